@@ -0,0 +1,73 @@
+//! Compares lookups by plain handles against lookups by pre-hashed handles
+//! on path-like string keys.
+//!
+//! Run with `cargo bench --bench hashed_lookup`.
+use std::{collections::HashMap, hint::black_box, time::Instant};
+
+use handle_key::{BuildKeyHasher, Handle, HashedHandle};
+
+struct Texture;
+
+const KEYS: usize = 10_000;
+const ROUNDS: usize = 100;
+
+fn path(i: usize) -> String {
+    format!(
+        "assets/textures/terrain/biome_{:02}/grass_variant_{i:05}.png",
+        i % 17
+    )
+}
+
+fn bench(name: &str, mut lookup: impl FnMut() -> usize) {
+    // warm up
+    black_box(lookup());
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        black_box(lookup());
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{name:<40} {:>8.2} ns/lookup",
+        elapsed.as_nanos() as f64 / (ROUNDS * KEYS) as f64
+    );
+}
+
+fn main() {
+    let handles = (0..KEYS)
+        .map(|i| Handle::<Texture>::new(path(i)))
+        .collect::<Vec<_>>();
+    let hashed = handles
+        .iter()
+        .cloned()
+        .map(Handle::hashed)
+        .collect::<Vec<_>>();
+
+    let plain_map = handles
+        .iter()
+        .cloned()
+        .enumerate()
+        .map(|(i, h)| (h, i))
+        .collect::<HashMap<Handle<Texture>, usize>>();
+    let plain_key_hasher_map = handles
+        .iter()
+        .cloned()
+        .enumerate()
+        .map(|(i, h)| (h, i))
+        .collect::<HashMap<Handle<Texture>, usize, BuildKeyHasher>>();
+    let hashed_map = hashed
+        .iter()
+        .cloned()
+        .enumerate()
+        .map(|(i, h)| (h, i))
+        .collect::<HashMap<HashedHandle<Texture>, usize, BuildKeyHasher>>();
+
+    bench("Handle, RandomState", || {
+        handles.iter().map(|h| plain_map[h]).sum()
+    });
+    bench("Handle, BuildKeyHasher", || {
+        handles.iter().map(|h| plain_key_hasher_map[h]).sum()
+    });
+    bench("HashedHandle, BuildKeyHasher", || {
+        hashed.iter().map(|h| hashed_map[h]).sum()
+    });
+}
@@ -0,0 +1,110 @@
+//! Derive macro for [handle-key](https://docs.rs/handle-key/).
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitStr};
+
+/// Derive `From<Self>` for `HandleKey` and `Handle<T>`, plus `KeyEnum` for
+/// looking the variant back up from a key.
+///
+/// Only enums with unit variants are supported. Each variant is keyed by its
+/// name, which can be overridden with `#[handle_key(name = "...")]`.
+#[proc_macro_derive(HandleKey, attributes(handle_key))]
+pub fn derive_handle_key(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn variant_name(variant: &syn::Variant) -> syn::Result<String> {
+    let mut name = variant.ident.to_string();
+    for attr in variant.attrs.iter() {
+        if !attr.path().is_ident("handle_key") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else {
+                Err(meta.error("expected `name = \"...\"`"))
+            }
+        })?;
+    }
+    Ok(name)
+}
+
+fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "HandleKey can only be derived for enums",
+        ));
+    };
+    if data.variants.is_empty() {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "HandleKey cannot be derived for enums without variants",
+        ));
+    }
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "HandleKey cannot be derived for generic enums",
+        ));
+    }
+
+    let ident = &input.ident;
+    let mut variants = vec![];
+    let mut names = vec![];
+    for variant in data.variants.iter() {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                variant,
+                "HandleKey can only be derived for enums with unit variants",
+            ));
+        }
+        let name = variant_name(variant)?;
+        if names.contains(&name) {
+            return Err(Error::new_spanned(
+                variant,
+                format!("duplicate handle key name {name:?}"),
+            ));
+        }
+        variants.push(&variant.ident);
+        names.push(name);
+    }
+
+    Ok(quote! {
+        impl ::handle_key::KeyEnum for #ident {
+            const VARIANTS: &'static [Self] = &[#(#ident::#variants),*];
+
+            fn key_name(&self) -> &'static str {
+                match self {
+                    #(#ident::#variants => #names,)*
+                }
+            }
+
+            fn from_key(key: &::handle_key::HandleKey) -> ::core::option::Option<Self> {
+                match key.as_str()? {
+                    #(#names => ::core::option::Option::Some(#ident::#variants),)*
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+
+        impl ::core::convert::From<#ident> for ::handle_key::HandleKey {
+            fn from(value: #ident) -> Self {
+                ::handle_key::HandleKey::Static(::handle_key::StaticKey::new(
+                    ::handle_key::KeyEnum::key_name(&value),
+                ))
+            }
+        }
+
+        impl<T> ::core::convert::From<#ident> for ::handle_key::Handle<T> {
+            fn from(value: #ident) -> Self {
+                ::handle_key::Handle::from_static(::handle_key::KeyEnum::key_name(&value))
+            }
+        }
+    })
+}
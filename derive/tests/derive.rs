@@ -0,0 +1,55 @@
+use handle_key::{Handle, HandleKey, KeyEnum};
+
+#[derive(Clone, Copy, Debug, PartialEq, HandleKey)]
+enum Layer {
+    Background,
+    #[handle_key(name = "fg")]
+    Foreground,
+    Ui,
+}
+
+struct Sprite;
+
+#[test]
+fn variants_are_keyed_by_name() {
+    assert_eq!(
+        Layer::VARIANTS,
+        &[Layer::Background, Layer::Foreground, Layer::Ui]
+    );
+    assert_eq!(Layer::Background.key_name(), "Background");
+    assert_eq!(Layer::Ui.key_name(), "Ui");
+}
+
+#[test]
+fn name_attribute_overrides_the_key() {
+    assert_eq!(Layer::Foreground.key_name(), "fg");
+    assert_eq!(HandleKey::from(Layer::Foreground), HandleKey::from("fg"));
+    assert_eq!(Layer::from_key(&"Foreground".into()), None);
+}
+
+#[test]
+fn keys_round_trip() {
+    for layer in Layer::VARIANTS {
+        let key = HandleKey::from(*layer);
+        assert_eq!(Layer::from_key(&key), Some(*layer));
+        assert_eq!(
+            Layer::from_key(&HandleKey::interned(layer.key_name())),
+            Some(*layer)
+        );
+    }
+    assert_eq!(Layer::from_key(&"Sky".into()), None);
+    assert_eq!(Layer::from_key(&0usize.into()), None);
+}
+
+#[test]
+fn handles_round_trip() {
+    let handle = Handle::<Sprite>::from(Layer::Ui);
+    assert_eq!(handle, Handle::new("Ui"));
+    assert!(!handle.is_tracked());
+    assert_eq!(handle.to_variant::<Layer>(), Some(Layer::Ui));
+    assert_eq!(
+        Handle::<Sprite>::new("fg").to_variant(),
+        Some(Layer::Foreground)
+    );
+    assert_eq!(Handle::<Sprite>::new("Sky").to_variant::<Layer>(), None);
+}
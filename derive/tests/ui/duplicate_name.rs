@@ -0,0 +1,8 @@
+#[derive(handle_key::HandleKey)]
+enum Layer {
+    Background,
+    #[handle_key(name = "Background")]
+    Backdrop,
+}
+
+fn main() {}
@@ -0,0 +1,4 @@
+#[derive(handle_key::HandleKey)]
+enum Empty {}
+
+fn main() {}
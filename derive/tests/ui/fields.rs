@@ -0,0 +1,7 @@
+#[derive(handle_key::HandleKey)]
+enum Layer {
+    Background,
+    Custom(u32),
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[derive(handle_key::HandleKey)]
+enum Layer<T> {
+    Background,
+    Unused(std::marker::PhantomData<T>),
+}
+
+fn main() {}
@@ -0,0 +1,4 @@
+#[derive(handle_key::HandleKey)]
+struct Layer;
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[derive(handle_key::HandleKey)]
+enum Layer {
+    #[handle_key(rename = "bg")]
+    Background,
+}
+
+fn main() {}
@@ -0,0 +1,41 @@
+//! Support for macros. Not public API.
+pub const fn ascii_lowercase<const N: usize>(s: &str) -> [u8; N] {
+    let bytes = s.as_bytes();
+    assert!(bytes.len() == N);
+    let mut lowercase = [0; N];
+    let mut i = 0;
+    while i < N {
+        lowercase[i] = bytes[i].to_ascii_lowercase();
+        i += 1;
+    }
+    lowercase
+}
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+pub const fn assert_unique_names(names: &[&str]) {
+    let mut i = 0;
+    while i < names.len() {
+        let mut j = i + 1;
+        while j < names.len() {
+            if str_eq(names[i], names[j]) {
+                panic!("duplicate handle name");
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+}
@@ -0,0 +1,238 @@
+//! Allocators that mint unique numeric handles.
+use std::{
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use crate::{Handle, Integer};
+
+fn handle_number<T>(handle: &Handle<T>) -> Option<usize> {
+    match handle.key.as_key_ref().as_integer()? {
+        Integer::NonNegative(n) => usize::try_from(n).ok(),
+        Integer::Negative(_) => None,
+    }
+}
+
+/// Hands out `Handle<T>`s with unique [`HandleKey::Number`](crate::HandleKey::Number) keys.
+///
+/// Freed numbers are reused before new ones are minted. Numbers below the
+/// allocator's floor, set by [`HandleAllocator::starting_at`] and
+/// [`HandleAllocator::reserve_below`], are never handed out, even if a handle
+/// with such a number is freed.
+pub struct HandleAllocator<T> {
+    floor: usize,
+    next: usize,
+    free: Vec<usize>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for HandleAllocator<T> {
+    fn default() -> Self {
+        Self::starting_at(0)
+    }
+}
+
+impl<T> std::fmt::Debug for HandleAllocator<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(&format!("HandleAllocator<{}>", std::any::type_name::<T>()))
+            .field("floor", &self.floor)
+            .field("next", &self.next)
+            .field("free", &self.free)
+            .finish()
+    }
+}
+
+impl<T> HandleAllocator<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an allocator that mints numbers starting at `next`.
+    pub fn starting_at(next: usize) -> Self {
+        HandleAllocator {
+            floor: next,
+            next,
+            free: vec![],
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Ensure that no number below `n` is handed out, e.g. because those
+    /// numbers are already used by loaded data.
+    pub fn reserve_below(&mut self, n: usize) {
+        self.floor = self.floor.max(n);
+        self.next = self.next.max(n);
+        self.free.retain(|free| *free >= n);
+    }
+
+    pub fn allocate(&mut self) -> Handle<T> {
+        let n = self.free.pop().unwrap_or_else(|| {
+            let n = self.next;
+            self.next += 1;
+            n
+        });
+        Handle::new(n)
+    }
+
+    /// Return the handle's number to the allocator for reuse.
+    ///
+    /// Returns `false` if the handle's number is outside the range this
+    /// allocator hands out or is already free. Only the number is checked, so
+    /// a handle minted elsewhere with a number in range is accepted. It is up
+    /// to the caller to make sure no other clones of the handle are still in
+    /// use.
+    pub fn free(&mut self, handle: Handle<T>) -> bool {
+        match handle_number(&handle) {
+            Some(n) if (self.floor..self.next).contains(&n) && !self.free.contains(&n) => {
+                self.free.push(n);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A thread-safe [`HandleAllocator`].
+pub struct AtomicHandleAllocator<T> {
+    floor: AtomicUsize,
+    next: AtomicUsize,
+    free: Mutex<Vec<usize>>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for AtomicHandleAllocator<T> {
+    fn default() -> Self {
+        Self::starting_at(0)
+    }
+}
+
+impl<T> std::fmt::Debug for AtomicHandleAllocator<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(&format!(
+            "AtomicHandleAllocator<{}>",
+            std::any::type_name::<T>()
+        ))
+        .field("floor", &self.floor)
+        .field("next", &self.next)
+        .field("free", &self.free)
+        .finish()
+    }
+}
+
+impl<T> AtomicHandleAllocator<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an allocator that mints numbers starting at `next`.
+    pub const fn starting_at(next: usize) -> Self {
+        AtomicHandleAllocator {
+            floor: AtomicUsize::new(next),
+            next: AtomicUsize::new(next),
+            free: Mutex::new(vec![]),
+            _phantom: PhantomData,
+        }
+    }
+
+    fn free_list(&self) -> std::sync::MutexGuard<'_, Vec<usize>> {
+        self.free.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Ensure that no number below `n` is handed out, e.g. because those
+    /// numbers are already used by loaded data.
+    pub fn reserve_below(&self, n: usize) {
+        let mut free = self.free_list();
+        self.floor.fetch_max(n, Ordering::Relaxed);
+        self.next.fetch_max(n, Ordering::Relaxed);
+        free.retain(|free| *free >= n);
+    }
+
+    pub fn allocate(&self) -> Handle<T> {
+        let n = self
+            .free_list()
+            .pop()
+            .unwrap_or_else(|| self.next.fetch_add(1, Ordering::Relaxed));
+        Handle::new(n)
+    }
+
+    /// Return the handle's number to the allocator for reuse.
+    ///
+    /// Returns `false` if the handle's number is outside the range this
+    /// allocator hands out or is already free. Only the number is checked, so
+    /// a handle minted elsewhere with a number in range is accepted. It is up
+    /// to the caller to make sure no other clones of the handle are still in
+    /// use.
+    pub fn free(&self, handle: Handle<T>) -> bool {
+        // The floor only changes while the free list is locked.
+        let mut free = self.free_list();
+        let floor = self.floor.load(Ordering::Relaxed);
+        let next = self.next.load(Ordering::Relaxed);
+        match handle_number(&handle) {
+            Some(n) if (floor..next).contains(&n) && !free.contains(&n) => {
+                free.push(n);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Tex;
+
+    #[test]
+    fn freed_numbers_are_reused() {
+        let mut allocator = HandleAllocator::<Tex>::new();
+        let a = allocator.allocate();
+        let b = allocator.allocate();
+        assert_ne!(a, b);
+        assert!(allocator.free(a.clone()));
+        assert!(!allocator.free(a.clone()));
+        assert_eq!(allocator.allocate(), a);
+        assert_eq!(allocator.allocate(), Handle::new(2usize));
+    }
+
+    #[test]
+    fn free_accepts_any_integer_key() {
+        let mut allocator = HandleAllocator::<Tex>::new();
+        allocator.allocate();
+        assert!(allocator.free(Handle::new(0)));
+        assert!(!allocator.free(Handle::new(-1i64)));
+        assert!(!allocator.free(Handle::new("0")));
+    }
+
+    #[test]
+    fn numbers_below_the_floor_are_never_handed_out() {
+        let mut allocator = HandleAllocator::<Tex>::starting_at(100);
+        assert!(!allocator.free(Handle::new(5usize)));
+        assert_eq!(allocator.allocate(), Handle::new(100usize));
+
+        let mut allocator = HandleAllocator::<Tex>::new();
+        for _ in 0..10 {
+            allocator.allocate();
+        }
+        assert!(allocator.free(Handle::new(3usize)));
+        allocator.reserve_below(20);
+        assert!(!allocator.free(Handle::new(5usize)));
+        assert_eq!(allocator.allocate(), Handle::new(20usize));
+    }
+
+    #[test]
+    fn atomic_numbers_below_the_floor_are_never_handed_out() {
+        let allocator = AtomicHandleAllocator::<Tex>::starting_at(100);
+        assert!(!allocator.free(Handle::new(5usize)));
+        let a = allocator.allocate();
+        assert_eq!(a, Handle::new(100usize));
+        assert!(allocator.free(a));
+        allocator.reserve_below(200);
+        assert!(!allocator.free(Handle::new(150usize)));
+        assert_eq!(allocator.allocate(), Handle::new(200usize));
+        assert!(allocator.free(Handle::new(200)));
+    }
+}
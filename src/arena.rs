@@ -0,0 +1,274 @@
+//! A slot map that hands out generational handles.
+use crate::{Handle, HandleKey};
+
+/// Returns the slot index of a handle allocated by an arena.
+fn arena_index<T>(handle: &Handle<T>) -> usize {
+    match handle.key {
+        HandleKey::Generational { index, .. } => index,
+        _ => unreachable!("arena handles are generational"),
+    }
+}
+
+struct Slot<T> {
+    generation: u32,
+    entry: Option<(Handle<T>, T)>,
+}
+
+/// A slot map of `T` that allocates its own handles.
+///
+/// Each handle is a [`HandleKey::Generational`] key. When an entry is removed
+/// its slot's generation is bumped before the slot is reused, so stale
+/// handles to the removed entry resolve to `None` instead of to whatever
+/// took its place. A slot whose generation has run out is retired rather than
+/// wrapping around to a generation that stale handles might still hold.
+pub struct HandleArena<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<usize>,
+    len: usize,
+}
+
+impl<T> Default for HandleArena<T> {
+    fn default() -> Self {
+        HandleArena {
+            slots: vec![],
+            free: vec![],
+            len: 0,
+        }
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for HandleArena<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T> HandleArena<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        HandleArena {
+            slots: Vec::with_capacity(capacity),
+            free: vec![],
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Insert a value into a free slot, returning a new handle to it.
+    pub fn insert(&mut self, value: T) -> Handle<T> {
+        let index = self.free.pop().unwrap_or_else(|| {
+            self.slots.push(Slot {
+                generation: 0,
+                entry: None,
+            });
+            self.slots.len() - 1
+        });
+        let slot = &mut self.slots[index];
+        let handle = Handle::new(HandleKey::Generational {
+            index,
+            generation: slot.generation,
+        });
+        slot.entry = Some((handle.clone(), value));
+        self.len += 1;
+        handle
+    }
+
+    fn slot_index(&self, handle: &Handle<T>) -> Option<usize> {
+        match handle.key {
+            HandleKey::Generational { index, generation } => {
+                let slot = self.slots.get(index)?;
+                (slot.generation == generation && slot.entry.is_some()).then_some(index)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns whether the handle refers to a live entry.
+    pub fn contains(&self, handle: &Handle<T>) -> bool {
+        self.slot_index(handle).is_some()
+    }
+
+    pub fn get(&self, handle: &Handle<T>) -> Option<&T> {
+        let index = self.slot_index(handle)?;
+        self.slots[index].entry.as_ref().map(|(_, value)| value)
+    }
+
+    pub fn get_mut(&mut self, handle: &Handle<T>) -> Option<&mut T> {
+        let index = self.slot_index(handle)?;
+        self.slots[index].entry.as_mut().map(|(_, value)| value)
+    }
+
+    /// Remove an entry, freeing its slot for reuse.
+    ///
+    /// Returns `None` if the handle is stale or was not allocated by this
+    /// arena.
+    pub fn remove(&mut self, handle: &Handle<T>) -> Option<T> {
+        let index = self.slot_index(handle)?;
+        Some(self.remove_at(index).1)
+    }
+
+    fn remove_at(&mut self, index: usize) -> (Handle<T>, T) {
+        let entry = self.slots[index].entry.take().expect("slot is occupied");
+        self.vacate(index);
+        entry
+    }
+
+    /// Bumps the generation of a slot whose entry has been taken, freeing it
+    /// for reuse unless the generation has run out.
+    fn vacate(&mut self, index: usize) {
+        let slot = &mut self.slots[index];
+        if let Some(generation) = slot.generation.checked_add(1) {
+            slot.generation = generation;
+            self.free.push(index);
+        }
+        self.len -= 1;
+    }
+
+    pub fn clear(&mut self) {
+        for index in 0..self.slots.len() {
+            if self.slots[index].entry.is_some() {
+                self.remove_at(index);
+            }
+        }
+    }
+
+    pub fn retain(&mut self, mut f: impl FnMut(&Handle<T>, &mut T) -> bool) {
+        for index in 0..self.slots.len() {
+            if let Some((handle, value)) = self.slots[index].entry.as_mut() {
+                if !f(handle, value) {
+                    self.remove_at(index);
+                }
+            }
+        }
+    }
+
+    /// Remove every entry whose handle is no longer held by anything outside
+    /// of this arena, returning the evicted handles and values.
+    ///
+    /// Weak handles to an evicted entry can no longer be upgraded.
+    pub fn collect_unreferenced(&mut self) -> Vec<(Handle<T>, T)> {
+        let candidates = self
+            .slots
+            .iter_mut()
+            .filter_map(|slot| slot.entry.take_if(|(handle, _)| handle.is_unreferenced()))
+            .collect::<Vec<_>>();
+        let collected = crate::collect_unreferenced(candidates, |handle, value| {
+            let index = arena_index(&handle);
+            self.slots[index].entry = Some((handle, value));
+        });
+        for (handle, _) in &collected {
+            self.vacate(arena_index(handle));
+        }
+        collected
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Handle<T>, &T)> {
+        self.slots
+            .iter()
+            .filter_map(|slot| slot.entry.as_ref().map(|(handle, value)| (handle, value)))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&Handle<T>, &mut T)> {
+        self.slots
+            .iter_mut()
+            .filter_map(|slot| slot.entry.as_mut().map(|(handle, value)| (&*handle, value)))
+    }
+
+    pub fn handles(&self) -> impl Iterator<Item = &Handle<T>> {
+        self.iter().map(|(handle, _)| handle)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.iter().map(|(_, value)| value)
+    }
+}
+
+impl<T> std::ops::Index<&Handle<T>> for HandleArena<T> {
+    type Output = T;
+
+    fn index(&self, handle: &Handle<T>) -> &T {
+        self.get(handle)
+            .unwrap_or_else(|| panic!("no live entry for {handle:?}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_handles_resolve_to_none() {
+        let mut arena = HandleArena::new();
+        let a = arena.insert("a");
+        assert_eq!(arena.remove(&a), Some("a"));
+        assert!(!arena.contains(&a));
+        assert_eq!(arena.get(&a), None);
+        assert_eq!(arena.remove(&a), None);
+
+        // The slot is reused, but the stale handle still doesn't resolve.
+        let b = arena.insert("b");
+        assert_eq!(
+            b.key(),
+            &HandleKey::Generational {
+                index: 0,
+                generation: 1
+            }
+        );
+        assert_eq!(arena.get(&a), None);
+        assert_eq!(arena[&b], "b");
+        assert_eq!(arena.len(), 1);
+    }
+
+    #[test]
+    fn exhausted_slots_are_retired() {
+        let mut arena = HandleArena::new();
+        let a = arena.insert("a");
+        arena.slots[0].generation = u32::MAX;
+        arena.slots[0].entry.as_mut().unwrap().0 = Handle::new(HandleKey::Generational {
+            index: 0,
+            generation: u32::MAX,
+        });
+        let last = arena.handles().next().unwrap().clone();
+        assert_eq!(arena.remove(&last), Some("a"));
+
+        let b = arena.insert("b");
+        assert_eq!(
+            b.key(),
+            &HandleKey::Generational {
+                index: 1,
+                generation: 0
+            }
+        );
+        assert_eq!(arena.get(&a), None);
+        assert_eq!(arena.get(&last), None);
+    }
+
+    #[test]
+    fn foreign_handles_resolve_to_none() {
+        let mut arena = HandleArena::new();
+        arena.insert(1);
+        assert_eq!(arena.get(&Handle::new(0usize)), None);
+        assert_eq!(arena.get(&Handle::new("0")), None);
+        let out_of_range = Handle::new(HandleKey::Generational {
+            index: 5,
+            generation: 0,
+        });
+        assert_eq!(arena.get(&out_of_range), None);
+        let future = Handle::new(HandleKey::Generational {
+            index: 0,
+            generation: 3,
+        });
+        assert_eq!(arena.remove(&future), None);
+        assert_eq!(arena.len(), 1);
+    }
+}
@@ -0,0 +1,370 @@
+//! A sharded map keyed by typed handles that can be shared between threads.
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, RandomState},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use arc_swap::{ArcSwap, Guard};
+
+use crate::{AsKeyRef, Handle, KeyRef};
+
+type Map<T> = HashMap<Handle<T>, Arc<T>>;
+
+/// One shard of a [`ConcurrentHandleMap`].
+///
+/// Readers load the current map without locking. Writers take the lock, copy
+/// the map, change the copy and publish it in place of the current map.
+struct Shard<T> {
+    map: ArcSwap<Map<T>>,
+    writer: Mutex<()>,
+}
+
+impl<T> Default for Shard<T> {
+    fn default() -> Self {
+        Shard {
+            map: ArcSwap::default(),
+            writer: Mutex::default(),
+        }
+    }
+}
+
+impl<T> Shard<T> {
+    fn read(&self) -> Guard<Arc<Map<T>>> {
+        self.map.load()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ()> {
+        self.writer.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Change a copy of the map and publish it.
+    ///
+    /// The caller must hold the writer lock.
+    fn publish<R>(&self, f: impl FnOnce(&mut Map<T>) -> R) -> R {
+        let mut map = Map::clone(&self.map.load());
+        let result = f(&mut map);
+        self.map.store(Arc::new(map));
+        result
+    }
+
+    fn write<R>(&self, f: impl FnOnce(&mut Map<T>) -> R) -> R {
+        let _writer = self.lock();
+        self.publish(f)
+    }
+}
+
+/// A thread-safe map from `Handle<T>` to `Arc<T>`.
+///
+/// Entries are spread across a number of shards. Reads never lock: they load
+/// a snapshot of one shard, so they neither wait on each other nor on
+/// writers. Writes to the same shard are serialized, and each write copies
+/// the shard it changes, so a write costs time proportional to the size of
+/// its shard. The map is best suited to workloads that read far more often
+/// than they write, and values added in bulk should go through
+/// [`ConcurrentHandleMap::extend`], which copies each shard once. Writes to
+/// different shards proceed in parallel.
+pub struct ConcurrentHandleMap<T> {
+    hasher: RandomState,
+    shards: Box<[Shard<T>]>,
+}
+
+impl<T> Default for ConcurrentHandleMap<T> {
+    fn default() -> Self {
+        let shards = std::thread::available_parallelism()
+            .map_or(1, usize::from)
+            .saturating_mul(4)
+            .next_power_of_two();
+        Self::with_shards(shards)
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for ConcurrentHandleMap<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut map = f.debug_map();
+        for shard in self.shards.iter() {
+            map.entries(shard.read().iter());
+        }
+        map.finish()
+    }
+}
+
+impl<T> ConcurrentHandleMap<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a map with the given number of shards, rounded up to a power
+    /// of two.
+    pub fn with_shards(shards: usize) -> Self {
+        let shards = shards.max(1).next_power_of_two();
+        ConcurrentHandleMap {
+            hasher: RandomState::new(),
+            shards: (0..shards).map(|_| Shard::default()).collect(),
+        }
+    }
+
+    fn shard_index(&self, key: KeyRef<'_>) -> usize {
+        let hash = self.hasher.hash_one(key) as usize;
+        hash & (self.shards.len() - 1)
+    }
+
+    fn shard(&self, key: KeyRef<'_>) -> &Shard<T> {
+        &self.shards[self.shard_index(key)]
+    }
+
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| shard.read().len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(|shard| shard.read().is_empty())
+    }
+
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            let _writer = shard.lock();
+            shard.map.store(Arc::default());
+        }
+    }
+
+    /// Insert a value, returning the handle and value previously stored under
+    /// the same key.
+    ///
+    /// The map keeps the given handle as its key and hands the displaced one
+    /// back, as [`HandleMap::insert`](crate::HandleMap::insert) does.
+    pub fn insert(
+        &self,
+        handle: Handle<T>,
+        value: impl Into<Arc<T>>,
+    ) -> Option<(Handle<T>, Arc<T>)> {
+        let value = value.into();
+        self.shard(handle.key.as_key_ref()).write(|map| {
+            let previous = map.remove_entry(&handle);
+            map.insert(handle, value);
+            previous
+        })
+    }
+
+    /// Insert every value from `iter`, keeping the given handles as keys like
+    /// [`ConcurrentHandleMap::insert`] does.
+    ///
+    /// The values are grouped by shard first, so each shard is copied and
+    /// published once rather than once per value. Prefer this to calling
+    /// `insert` in a loop when adding many values at once.
+    pub fn extend<V: Into<Arc<T>>>(&self, iter: impl IntoIterator<Item = (Handle<T>, V)>) {
+        let mut batches = (0..self.shards.len()).map(|_| vec![]).collect::<Vec<_>>();
+        for (handle, value) in iter {
+            batches[self.shard_index(handle.key.as_key_ref())].push((handle, value.into()));
+        }
+        for (shard, batch) in self.shards.iter().zip(batches) {
+            if batch.is_empty() {
+                continue;
+            }
+            shard.write(|map| {
+                for (handle, value) in batch {
+                    map.remove(&handle);
+                    map.insert(handle, value);
+                }
+            });
+        }
+    }
+
+    /// Returns the value for `handle`, inserting the result of `f` first if
+    /// there is none.
+    ///
+    /// `f` is called while writes to the handle's shard are locked.
+    pub fn get_or_insert_with(&self, handle: Handle<T>, f: impl FnOnce() -> T) -> Arc<T> {
+        let shard = self.shard(handle.key.as_key_ref());
+        if let Some(value) = shard.read().get(&handle) {
+            return value.clone();
+        }
+        let _writer = shard.lock();
+        if let Some(value) = shard.read().get(&handle) {
+            return value.clone();
+        }
+        let value = Arc::new(f());
+        shard.publish(|map| map.insert(handle, value.clone()));
+        value
+    }
+
+    pub fn contains(&self, handle: &Handle<T>) -> bool {
+        self.shard(handle.key.as_key_ref())
+            .read()
+            .contains_key(handle)
+    }
+
+    pub fn get(&self, handle: &Handle<T>) -> Option<Arc<T>> {
+        self.shard(handle.key.as_key_ref())
+            .read()
+            .get(handle)
+            .cloned()
+    }
+
+    /// Look up a value by the name of its handle.
+    pub fn get_named(&self, name: &str) -> Option<Arc<T>> {
+        let key = KeyRef::Str(name);
+        self.shard(key).read().get(&key as &dyn AsKeyRef).cloned()
+    }
+
+    pub fn remove(&self, handle: &Handle<T>) -> Option<Arc<T>> {
+        let shard = self.shard(handle.key.as_key_ref());
+        let _writer = shard.lock();
+        if !shard.read().contains_key(handle) {
+            return None;
+        }
+        shard.publish(|map| map.remove(handle))
+    }
+
+    pub fn retain(&self, mut f: impl FnMut(&Handle<T>, &Arc<T>) -> bool) {
+        for shard in self.shards.iter() {
+            shard.write(|map| map.retain(|handle, value| f(handle, value)));
+        }
+    }
+
+    /// Remove every entry whose handle is no longer held by anything outside
+    /// of this map, returning the evicted handles and values.
+    ///
+    /// Weak handles to an evicted entry can no longer be upgraded, so an
+    /// entry is never evicted while a strong handle to it exists. Entries
+    /// that are being read while they are collected may be kept until the
+    /// next collection.
+    pub fn collect_unreferenced(&self) -> Vec<(Handle<T>, Arc<T>)> {
+        let mut collected = vec![];
+        for shard in self.shards.iter() {
+            let _writer = shard.lock();
+            let unreferenced = shard
+                .read()
+                .iter()
+                .filter(|(handle, _)| handle.is_unreferenced())
+                .map(|(handle, value)| (handle.clone(), value.clone()))
+                .collect::<Vec<_>>();
+            if unreferenced.is_empty() {
+                continue;
+            }
+            shard.publish(|map| {
+                for (handle, _) in &unreferenced {
+                    map.remove(handle);
+                }
+            });
+
+            // Once the previous copy of the map is gone the clones taken
+            // above are the only ones left, unless a weak handle was upgraded
+            // in the meantime, in which case the entry goes back in.
+            let mut kept = vec![];
+            collected.extend(crate::collect_unreferenced(
+                unreferenced,
+                |handle, value| kept.push((handle, value)),
+            ));
+            if !kept.is_empty() {
+                shard.publish(|map| map.extend(kept));
+            }
+        }
+        collected
+    }
+
+    /// Returns a snapshot of every handle in the map.
+    pub fn handles(&self) -> Vec<Handle<T>> {
+        self.shards
+            .iter()
+            .flat_map(|shard| shard.read().keys().cloned().collect::<Vec<_>>())
+            .collect()
+    }
+}
+
+impl<T> FromIterator<(Handle<T>, T)> for ConcurrentHandleMap<T> {
+    fn from_iter<I: IntoIterator<Item = (Handle<T>, T)>>(iter: I) -> Self {
+        let map = ConcurrentHandleMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_keeps_the_latest_handle() {
+        let map = ConcurrentHandleMap::<u32>::new();
+        map.insert(Handle::new("tex"), 1);
+        let tex = Handle::new("tex");
+        let (_, previous) = map.insert(tex.clone(), 2).unwrap();
+        assert_eq!(*previous, 1);
+        assert!(map.collect_unreferenced().is_empty());
+        assert_eq!(map.get(&tex).as_deref(), Some(&2));
+    }
+
+    #[test]
+    fn collect_unreferenced_evicts_unheld_entries() {
+        let map = ConcurrentHandleMap::<u32>::new();
+        let held = Handle::new("held");
+        map.insert(held.clone(), 1);
+        map.insert(Handle::new("dropped"), 2);
+        map.insert(Handle::from_static("static"), 3);
+        let evicted = map.collect_unreferenced();
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(*evicted[0].1, 2);
+        assert_eq!(map.len(), 2);
+        assert!(map.contains(&held));
+    }
+
+    #[test]
+    fn weak_handles_to_collected_entries_do_not_upgrade() {
+        let map = ConcurrentHandleMap::<u32>::new();
+        let weak = {
+            let handle = Handle::new("tex");
+            map.insert(handle.clone(), 1);
+            handle.downgrade()
+        };
+        let evicted = map.collect_unreferenced();
+        assert_eq!(evicted.len(), 1);
+        assert!(weak.upgrade().is_none());
+        assert_eq!(evicted[0].0.strong_count(), Some(1));
+    }
+
+    #[test]
+    fn entries_being_read_are_kept_until_the_next_collection() {
+        let map = ConcurrentHandleMap::<u32>::with_shards(1);
+        map.insert(Handle::new("tex"), 1);
+        let snapshot = map.shards[0].read();
+        assert!(map.collect_unreferenced().is_empty());
+        assert!(map.get_named("tex").is_some());
+        drop(snapshot);
+        assert_eq!(map.collect_unreferenced().len(), 1);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn extend_inserts_in_batches() {
+        let map = ConcurrentHandleMap::<usize>::with_shards(4);
+        map.insert(Handle::new(0usize), 1);
+        let zero = Handle::new(0usize);
+        map.extend((0..1000).map(|n| (Handle::new(n), n)));
+        map.extend([(zero.clone(), 7)]);
+        assert_eq!(map.len(), 1000);
+        assert_eq!(map.get(&zero).as_deref(), Some(&7));
+        assert_eq!(map.get(&Handle::new(999usize)).as_deref(), Some(&999));
+        drop(zero);
+        assert_eq!(map.collect_unreferenced().len(), 1000);
+    }
+
+    #[test]
+    fn reads_and_writes_from_many_threads() {
+        let map = ConcurrentHandleMap::<usize>::with_shards(4);
+        std::thread::scope(|scope| {
+            for thread in 0..4 {
+                let map = &map;
+                scope.spawn(move || {
+                    for n in 0..100 {
+                        let handle = Handle::new(thread * 100 + n);
+                        map.insert(handle.clone(), n);
+                        assert_eq!(map.get(&handle).as_deref(), Some(&n));
+                    }
+                });
+            }
+        });
+        assert_eq!(map.len(), 400);
+        assert_eq!(map.collect_unreferenced().len(), 400);
+    }
+}
@@ -0,0 +1,151 @@
+//! The shared reference count behind tracked handles.
+use std::{
+    marker::PhantomData,
+    sync::{mpsc::Sender, Arc},
+};
+
+use crate::{Handle, HandleKey};
+
+type OnDrop = Box<dyn FnOnce() + Send + Sync>;
+
+/// The value shared by every clone of a tracked [`Handle`].
+///
+/// When the last strong handle is dropped the count is dropped with it, which
+/// runs the drop callback, if one was registered at creation.
+///
+/// Handles derived from another handle, like sub-handles, have a count of
+/// their own that holds a reference to the count of the handle they were
+/// derived from. That keeps the original referenced for as long as the
+/// derived handle exists, without the two pinning each other when both are
+/// kept in stores.
+#[derive(Default)]
+pub struct RefCount {
+    on_drop: Option<OnDrop>,
+    parent: Option<Arc<RefCount>>,
+}
+
+impl std::fmt::Debug for RefCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RefCount")
+            .field("notifies_on_drop", &self.on_drop.is_some())
+            .field("has_parent", &self.parent.is_some())
+            .finish()
+    }
+}
+
+impl RefCount {
+    /// Returns a new count for a handle derived from one with `parent`'s
+    /// count, or `None` if `parent` is untracked.
+    pub(crate) fn derived(parent: &Option<Arc<RefCount>>) -> Option<Arc<RefCount>> {
+        parent.as_ref().map(|parent| {
+            Arc::new(RefCount {
+                on_drop: None,
+                parent: Some(parent.clone()),
+            })
+        })
+    }
+}
+
+impl Drop for RefCount {
+    fn drop(&mut self) {
+        if let Some(on_drop) = self.on_drop.take() {
+            on_drop();
+        }
+    }
+}
+
+impl<T> Handle<T> {
+    /// Create a tracked handle that calls `on_drop` with its key once the last
+    /// strong clone of the handle is dropped.
+    pub fn with_drop_callback<K>(
+        k: K,
+        on_drop: impl FnOnce(HandleKey) + Send + Sync + 'static,
+    ) -> Self
+    where
+        HandleKey: From<K>,
+    {
+        let key = HandleKey::from(k);
+        let notify_key = key.clone();
+        Handle {
+            key,
+            count: Some(Arc::new(RefCount {
+                on_drop: Some(Box::new(move || on_drop(notify_key))),
+                parent: None,
+            })),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Create a tracked handle that sends its key on `sender` once the last
+    /// strong clone of the handle is dropped.
+    ///
+    /// Nothing is sent if the receiver has hung up.
+    pub fn with_drop_sender<K>(k: K, sender: Sender<HandleKey>) -> Self
+    where
+        HandleKey: From<K>,
+    {
+        Handle::with_drop_callback(k, move |key| {
+            let _ = sender.send(key);
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::channel;
+
+    use super::*;
+
+    #[test]
+    fn notifies_once_after_the_last_clone_drops() {
+        let (sender, receiver) = channel();
+        let handle = Handle::<u32>::with_drop_sender("tex", sender);
+        let clone = handle.clone();
+        drop(handle);
+        assert!(receiver.try_recv().is_err());
+        drop(clone);
+        assert_eq!(receiver.try_recv(), Ok(HandleKey::from("tex")));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn upgraded_weak_handles_keep_the_notification_back() {
+        let (sender, receiver) = channel();
+        let handle = Handle::<u32>::with_drop_sender("tex", sender);
+        let weak = handle.downgrade();
+        let upgraded = weak.upgrade().unwrap();
+        drop(handle);
+        assert!(receiver.try_recv().is_err());
+        drop(upgraded);
+        assert_eq!(receiver.try_recv(), Ok(HandleKey::from("tex")));
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn derived_handles_keep_the_notification_back() {
+        let (sender, receiver) = channel();
+        let handle = Handle::<u32>::with_drop_sender("tex", sender);
+        let cast = handle.cast::<u8>();
+        drop(handle);
+        assert!(receiver.try_recv().is_err());
+        drop(cast);
+        assert_eq!(receiver.try_recv(), Ok(HandleKey::from("tex")));
+    }
+
+    #[test]
+    fn a_hung_up_receiver_is_ignored() {
+        let (sender, receiver) = channel();
+        let handle = Handle::<u32>::with_drop_sender("tex", sender);
+        drop(receiver);
+        drop(handle);
+    }
+
+    #[test]
+    fn calls_the_drop_callback_with_the_key() {
+        let (sender, receiver) = channel();
+        drop(Handle::<u32>::with_drop_callback(7usize, move |key| {
+            sender.send(key).unwrap()
+        }));
+        assert_eq!(receiver.try_recv(), Ok(HandleKey::Number(7)));
+    }
+}
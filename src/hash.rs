@@ -0,0 +1,319 @@
+//! Hashing shared by all handle keys.
+use std::{
+    borrow::Borrow,
+    hash::{BuildHasher, Hash, Hasher, RandomState},
+    ops::Deref,
+    sync::OnceLock,
+};
+
+use crate::{AsKeyRef, Handle, Integer, KeyRef};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Scramble the bits of `x` so that every input bit affects the high bits
+/// of the result (the splitmix64 finalizer).
+const fn mix(mut x: u64) -> u64 {
+    x ^= x >> 30;
+    x = x.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x ^= x >> 27;
+    x = x.wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// Returns the 64-bit FNV-1a fingerprint of a string, in a `const fn`.
+///
+/// [`StaticKey`] uses the fingerprint to compare static names with a single
+/// integer comparison. It is not seeded, so strings with the same fingerprint
+/// are easy to construct, and keys are not hashed with it.
+pub const fn fingerprint(s: &str) -> u64 {
+    let bytes = s.as_bytes();
+    let mut hash = FNV_OFFSET_BASIS;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+        i += 1;
+    }
+    mix(hash)
+}
+
+/// Returns the hash of a string key's text.
+///
+/// The hash is keyed with a random per-process seed, so names that collide
+/// can't be crafted ahead of time, e.g. in asset paths read from untrusted
+/// files.
+pub(crate) fn string_hash(s: &str) -> u64 {
+    static SEED: OnceLock<RandomState> = OnceLock::new();
+    SEED.get_or_init(RandomState::new).hash_one(s)
+}
+
+impl KeyRef<'_> {
+    /// Returns the 64-bit hash of this key.
+    ///
+    /// This is the only value a key writes when it is hashed. String keys are
+    /// hashed with a random per-process seed, so key hashes differ between
+    /// runs and must not be persisted.
+    pub fn key_hash(&self) -> u64 {
+        match self {
+            KeyRef::Str(s) => string_hash(s),
+            KeyRef::Interned(sym) => sym.key_hash(),
+            KeyRef::Static(key) => string_hash(key.as_str()),
+            #[cfg(feature = "uuid")]
+            KeyRef::Uuid(uuid) => {
+                let (hi, lo) = uuid.as_u64_pair();
+                mix(lo ^ mix(hi ^ FNV_PRIME))
+            }
+            KeyRef::Composite(keys) => keys.iter().fold(FNV_PRIME, |hash, key| {
+                mix(hash.rotate_left(5) ^ key.key_hash())
+            }),
+            KeyRef::Labeled(key) => {
+                mix(key.base().key_hash() ^ string_hash(key.label()).rotate_left(17))
+            }
+            KeyRef::Number(_) | KeyRef::U64(_) | KeyRef::U128(_) | KeyRef::I64(_) => {
+                match self.as_integer() {
+                    Some(Integer::NonNegative(n)) if n <= u64::MAX as u128 => mix(n as u64),
+                    Some(Integer::NonNegative(n)) => mix(n as u64 ^ mix((n >> 64) as u64)),
+                    Some(Integer::Negative(n)) => mix(n as u64 ^ FNV_OFFSET_BASIS.rotate_left(32)),
+                    None => unreachable!("integer keys have a value"),
+                }
+            }
+            KeyRef::Generational { index, generation } => {
+                mix(*index as u64 ^ (*generation as u64).rotate_right(16) ^ FNV_OFFSET_BASIS)
+            }
+        }
+    }
+}
+
+/// A static string along with its [`fingerprint`], computed in a `const fn`.
+///
+/// Two static keys compare by fingerprint alone. In debug builds equal
+/// fingerprints are checked against the names to catch collisions.
+///
+/// The fingerprint only speeds up comparisons. As a
+/// [`HandleKey::Static`](crate::HandleKey::Static) key, the name is hashed at
+/// lookup time with the same seeded hash as other string keys.
+#[derive(Clone, Copy, Debug)]
+pub struct StaticKey {
+    name: &'static str,
+    hash: u64,
+}
+
+impl PartialEq for StaticKey {
+    fn eq(&self, other: &Self) -> bool {
+        let eq = self.hash == other.hash;
+        debug_assert!(
+            !eq || self.name == other.name,
+            "static keys {:?} and {:?} have the same fingerprint",
+            self.name,
+            other.name
+        );
+        eq
+    }
+}
+
+impl Eq for StaticKey {}
+
+impl Hash for StaticKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+impl StaticKey {
+    pub const fn new(name: &'static str) -> Self {
+        StaticKey {
+            name,
+            hash: fingerprint(name),
+        }
+    }
+
+    pub const fn as_str(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the [`fingerprint`] of the name.
+    pub const fn fingerprint(&self) -> u64 {
+        self.hash
+    }
+}
+
+/// A [`Hasher`] that passes a key's precomputed hash straight through.
+///
+/// Keys in this crate hash as a single [`key_hash`](KeyRef::key_hash), which
+/// this hasher returns as-is instead of hashing it again. Other data is
+/// combined with FNV-1a.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KeyHasher {
+    hash: u64,
+}
+
+impl Hasher for KeyHasher {
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.hash ^= *byte as u64;
+            self.hash = self.hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.hash = self.hash.rotate_left(5) ^ n;
+    }
+}
+
+/// Builds [`KeyHasher`]s, for use with [`HandleMap`](crate::HandleMap) or
+/// `HashMap`.
+///
+/// Pairs best with [`HashedHandle`] keys, whose hash is computed once up
+/// front.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BuildKeyHasher;
+
+impl BuildHasher for BuildKeyHasher {
+    type Hasher = KeyHasher;
+
+    fn build_hasher(&self) -> KeyHasher {
+        KeyHasher::default()
+    }
+}
+
+/// A handle that caches the hash of its key, created with
+/// [`Handle::hashed`].
+///
+/// Hashes and compares the same as the `Handle` it wraps, so maps keyed by
+/// `HashedHandle<T>` can still be queried with a `&Handle<T>`.
+pub struct HashedHandle<T> {
+    handle: Handle<T>,
+    hash: u64,
+}
+
+impl<T> std::fmt::Debug for HashedHandle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.handle.fmt(f)
+    }
+}
+
+impl<T> Clone for HashedHandle<T> {
+    fn clone(&self) -> Self {
+        HashedHandle {
+            handle: self.handle.clone(),
+            hash: self.hash,
+        }
+    }
+}
+
+impl<T> Hash for HashedHandle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+impl<T> PartialEq for HashedHandle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.handle == other.handle
+    }
+}
+
+impl<T> Eq for HashedHandle<T> {}
+
+impl<T> Deref for HashedHandle<T> {
+    type Target = Handle<T>;
+
+    fn deref(&self) -> &Handle<T> {
+        &self.handle
+    }
+}
+
+impl<T> Borrow<Handle<T>> for HashedHandle<T> {
+    fn borrow(&self) -> &Handle<T> {
+        &self.handle
+    }
+}
+
+impl<'a, T> Borrow<dyn AsKeyRef + 'a> for HashedHandle<T> {
+    fn borrow(&self) -> &(dyn AsKeyRef + 'a) {
+        &self.handle.key
+    }
+}
+
+impl<T> From<Handle<T>> for HashedHandle<T> {
+    fn from(handle: Handle<T>) -> Self {
+        handle.hashed()
+    }
+}
+
+impl<T> HashedHandle<T> {
+    /// Returns the cached hash of the key.
+    pub fn key_hash(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn into_handle(self) -> Handle<T> {
+        self.handle
+    }
+}
+
+impl<T> Handle<T> {
+    /// Compute the hash of this handle's key once, up front.
+    pub fn hashed(self) -> HashedHandle<T> {
+        HashedHandle {
+            hash: self.key.as_key_ref().key_hash(),
+            handle: self,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{HandleKey, Symbol};
+
+    fn string_keys(name: &'static str) -> [HandleKey; 4] {
+        [
+            HandleKey::Str(name),
+            HandleKey::String(name.into()),
+            HandleKey::Interned(Symbol::intern(name)),
+            HandleKey::Static(StaticKey::new(name)),
+        ]
+    }
+
+    #[test]
+    fn string_keys_with_the_same_text_hash_the_same() {
+        let hasher = RandomState::new();
+        for a in string_keys("textures/grass.png") {
+            for b in string_keys("textures/grass.png") {
+                assert_eq!(a, b);
+                assert_eq!(a.key_hash(), b.key_hash());
+                assert_eq!(hasher.hash_one(&a), hasher.hash_one(&b));
+            }
+            for b in string_keys("textures/water.png") {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn string_keys_are_not_hashed_by_fingerprint() {
+        let key = HandleKey::from("grass");
+        assert_ne!(key.key_hash(), fingerprint("grass"));
+    }
+
+    #[test]
+    fn hashed_handles_are_found_by_handle() {
+        struct Tex;
+
+        let mut map = HashMap::with_hasher(BuildKeyHasher);
+        for key in string_keys("grass") {
+            map.insert(Handle::<Tex>::new(key).hashed(), 1);
+        }
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get(&Handle::new("grass")), Some(&1));
+        assert_eq!(map.get(&KeyRef::Str("grass") as &dyn AsKeyRef), Some(&1));
+    }
+}
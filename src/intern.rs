@@ -0,0 +1,98 @@
+//! A global string interner for cheap string keys.
+use std::{
+    collections::HashSet,
+    hash::{Hash, Hasher},
+    sync::{OnceLock, RwLock},
+};
+
+use crate::hash::string_hash;
+
+fn interner() -> &'static RwLock<HashSet<&'static str>> {
+    static INTERNER: OnceLock<RwLock<HashSet<&'static str>>> = OnceLock::new();
+    INTERNER.get_or_init(Default::default)
+}
+
+/// An interned string.
+///
+/// Symbols hold the interned text along with its hash, so comparing and
+/// hashing them never touches the string, and reading the text back never
+/// touches the interner. Interned strings are never freed.
+#[derive(Clone, Copy)]
+pub struct Symbol {
+    name: &'static str,
+    hash: u64,
+}
+
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Self) -> bool {
+        // Every symbol with the same text points at the same interned string.
+        std::ptr::eq(self.name, other.name)
+    }
+}
+
+impl Eq for Symbol {}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+impl std::fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Symbol").field(&self.name).finish()
+    }
+}
+
+impl Symbol {
+    /// Intern `name`, returning the same symbol for every call with the same
+    /// text.
+    pub fn intern(name: &str) -> Symbol {
+        let hash = string_hash(name);
+        if let Some(name) = interner()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(name)
+        {
+            return Symbol { name, hash };
+        }
+
+        let mut interner = interner().write().unwrap_or_else(|e| e.into_inner());
+        if let Some(name) = interner.get(name) {
+            return Symbol { name, hash };
+        }
+        let name: &'static str = Box::leak(name.into());
+        interner.insert(name);
+        Symbol { name, hash }
+    }
+
+    /// Returns the interned text.
+    pub fn as_str(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the cached [`key_hash`](crate::KeyRef::key_hash) of the text.
+    pub(crate) fn key_hash(&self) -> u64 {
+        self.hash
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(name: &str) -> Self {
+        Symbol::intern(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symbols_with_the_same_text_are_equal() {
+        let a = Symbol::intern("grass");
+        let b = Symbol::from(String::from("grass").as_str());
+        assert_eq!(a, b);
+        assert_eq!(a.as_str(), "grass");
+        assert_ne!(a, Symbol::intern("water"));
+    }
+}
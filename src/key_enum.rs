@@ -0,0 +1,25 @@
+//! Closed sets of keys backed by enums.
+use crate::{Handle, HandleKey};
+
+/// An enum whose variants each stand for a static key.
+///
+/// Usually implemented with `#[derive(HandleKey)]`, which requires the
+/// `derive` feature and also provides `From<Self>` for `HandleKey` and
+/// `Handle<T>`.
+pub trait KeyEnum: Sized + 'static {
+    /// Every variant, in declaration order.
+    const VARIANTS: &'static [Self];
+
+    /// Returns the name this variant is keyed by.
+    fn key_name(&self) -> &'static str;
+
+    /// Look a variant up by key.
+    fn from_key(key: &HandleKey) -> Option<Self>;
+}
+
+impl<T> Handle<T> {
+    /// Returns the variant of `E` this handle's key stands for, if any.
+    pub fn to_variant<E: KeyEnum>(&self) -> Option<E> {
+        E::from_key(&self.key)
+    }
+}
@@ -0,0 +1,124 @@
+//! Sub-asset keys made of a base key and a label, like `"model.gltf#Mesh0"`.
+use std::{marker::PhantomData, sync::Arc};
+
+use crate::{Handle, HandleKey, RefCount};
+
+/// A base key plus a label naming one of several assets produced from it.
+///
+/// Written `base#label` by the `Display` impl of
+/// [`HandleKey`](enum@HandleKey), and parsed back by its `FromStr` impl.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LabeledKey {
+    base: HandleKey,
+    label: String,
+}
+
+impl LabeledKey {
+    pub fn new(base: impl Into<HandleKey>, label: impl Into<String>) -> Self {
+        LabeledKey {
+            base: base.into(),
+            label: label.into(),
+        }
+    }
+
+    pub fn base(&self) -> &HandleKey {
+        &self.base
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+}
+
+impl From<LabeledKey> for HandleKey {
+    fn from(key: LabeledKey) -> Self {
+        HandleKey::Labeled(Arc::new(key))
+    }
+}
+
+impl HandleKey {
+    /// Returns a key for the asset labeled `label` within this one.
+    pub fn with_label(&self, label: impl Into<String>) -> HandleKey {
+        LabeledKey::new(self.clone(), label).into()
+    }
+
+    /// Returns the key without its label.
+    pub fn base(&self) -> &HandleKey {
+        match self {
+            HandleKey::Labeled(key) => key.base(),
+            key => key,
+        }
+    }
+
+    /// Returns the key without any of its labels, e.g. `a.gltf` for
+    /// `a.gltf#Mesh0#Primitive1`, where [`HandleKey::base`] returns
+    /// `a.gltf#Mesh0`.
+    pub fn root(&self) -> &HandleKey {
+        let mut key = self;
+        while let HandleKey::Labeled(labeled) = key {
+            key = labeled.base();
+        }
+        key
+    }
+
+    /// Returns the label, if this is a labeled key.
+    pub fn label(&self) -> Option<&str> {
+        match self {
+            HandleKey::Labeled(key) => Some(key.label()),
+            _ => None,
+        }
+    }
+}
+
+impl<T> Handle<T> {
+    /// Create a handle to the asset labeled `label` within this one, e.g. a
+    /// `Handle<Mesh>` within a `Handle<Scene>`.
+    ///
+    /// The sub-handle has a reference count of its own, and keeps the parent
+    /// referenced for as long as it exists. Once the sub-handle is only held
+    /// by a store it can be collected, after which the parent can be too.
+    pub fn sub_handle<U>(&self, label: impl Into<String>) -> Handle<U> {
+        Handle {
+            key: self.key.with_label(label),
+            count: RefCount::derived(&self.count),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HandleMap;
+
+    struct Scene;
+    struct Mesh;
+
+    #[test]
+    fn sub_handles_keep_their_parent_referenced() {
+        let scene = Handle::<Scene>::new("scene.gltf");
+        let mesh = scene.sub_handle::<Mesh>("Mesh0");
+        assert_eq!(
+            mesh.key(),
+            &HandleKey::from("scene.gltf").with_label("Mesh0")
+        );
+        assert_eq!(scene.strong_count(), Some(2));
+        assert_eq!(mesh.strong_count(), Some(1));
+        drop(mesh);
+        assert_eq!(scene.strong_count(), Some(1));
+    }
+
+    #[test]
+    fn stored_sub_handles_do_not_pin_their_parent() {
+        let mut scenes = HandleMap::new();
+        let mut meshes = HandleMap::new();
+        {
+            let scene = Handle::<Scene>::new("scene.gltf");
+            meshes.insert(scene.sub_handle("Mesh0"), Mesh);
+            scenes.insert(scene, Scene);
+        }
+        assert!(scenes.collect_unreferenced().is_empty());
+        assert_eq!(meshes.collect_unreferenced().len(), 1);
+        assert_eq!(scenes.collect_unreferenced().len(), 1);
+    }
+}
@@ -1,12 +1,343 @@
 //! Keys that serve as a means of accessing an object in a map.
-use std::{hash::Hash, marker::PhantomData, sync::Arc};
+use std::{borrow::Borrow, hash::Hash, marker::PhantomData, sync::Arc};
+
+mod allocator;
+pub use allocator::{AtomicHandleAllocator, HandleAllocator};
+
+mod arena;
+pub use arena::HandleArena;
+
+mod concurrent;
+pub use concurrent::ConcurrentHandleMap;
+
+mod count;
+pub(crate) use count::RefCount;
+
+mod hash;
+pub use hash::{fingerprint, BuildKeyHasher, HashedHandle, KeyHasher, StaticKey};
+
+mod intern;
+pub use intern::Symbol;
+
+mod key_enum;
+pub use key_enum::KeyEnum;
+
+#[cfg(feature = "derive")]
+pub use handle_key_derive::HandleKey;
+
+mod label;
+pub use label::LabeledKey;
+
+mod macros;
+
+mod map;
+pub use map::{Entry, HandleMap};
+
+mod ordered;
+pub use ordered::OrderedHandleMap;
+
+mod path;
+pub use path::KeyPath;
+
+#[doc(hidden)]
+pub mod __private;
+
+#[cfg(feature = "serde")]
+mod serde;
+
+mod registry;
+pub use registry::HandleRegistry;
+
+mod text;
+pub use text::ParseKeyError;
+
+mod untyped;
+pub use untyped::UntypedHandle;
+
+mod weak;
+pub use weak::WeakHandle;
 
 /// A handle key.
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+///
+/// `Str`, `String`, `Interned` and `Static` keys with the same text are equal
+/// and hash the same, as are integer keys with the same value, regardless of
+/// their width.
+///
+/// New kinds of keys may be added, and some variants depend on cargo
+/// features, so matches on `HandleKey` need a wildcard arm.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
 pub enum HandleKey {
     Str(&'static str),
     String(String),
     Number(usize),
+    U64(u64),
+    U128(u128),
+    I64(i64),
+    #[cfg(feature = "uuid")]
+    Uuid(uuid::Uuid),
+    /// A key made up of other keys, e.g. a pair of chunk coordinates.
+    Composite(Arc<[HandleKey]>),
+    /// A sub-asset key, see [`LabeledKey`].
+    Labeled(Arc<LabeledKey>),
+    /// A string interned in the global [`Symbol`] interner.
+    Interned(Symbol),
+    /// A static string with a fingerprint computed in a `const fn`, see
+    /// [`Handle::from_static`].
+    Static(StaticKey),
+    /// A slot index paired with the generation of the slot when the key was
+    /// created, as handed out by [`HandleArena`].
+    Generational {
+        index: usize,
+        generation: u32,
+    },
+}
+
+impl HandleKey {
+    /// Returns a borrowed view of this key, used for comparison and hashing.
+    pub fn as_key_ref(&self) -> KeyRef<'_> {
+        match self {
+            HandleKey::Str(s) => KeyRef::Str(s),
+            HandleKey::String(s) => KeyRef::Str(s),
+            HandleKey::Number(n) => KeyRef::Number(*n),
+            HandleKey::U64(n) => KeyRef::U64(*n),
+            HandleKey::U128(n) => KeyRef::U128(*n),
+            HandleKey::I64(n) => KeyRef::I64(*n),
+            #[cfg(feature = "uuid")]
+            HandleKey::Uuid(uuid) => KeyRef::Uuid(*uuid),
+            HandleKey::Composite(keys) => KeyRef::Composite(keys),
+            HandleKey::Labeled(key) => KeyRef::Labeled(key),
+            HandleKey::Interned(sym) => KeyRef::Interned(*sym),
+            HandleKey::Static(key) => KeyRef::Static(*key),
+            HandleKey::Generational { index, generation } => KeyRef::Generational {
+                index: *index,
+                generation: *generation,
+            },
+        }
+    }
+
+    /// Create a key made up of other keys.
+    pub fn composite<K>(keys: impl IntoIterator<Item = K>) -> Self
+    where
+        HandleKey: From<K>,
+    {
+        HandleKey::Composite(keys.into_iter().map(HandleKey::from).collect())
+    }
+
+    /// Create a key by interning `name`.
+    pub fn interned(name: &str) -> Self {
+        HandleKey::Interned(Symbol::intern(name))
+    }
+
+    /// Returns the name of this key, if it is a string key.
+    pub fn as_str(&self) -> Option<&str> {
+        self.as_key_ref().as_str()
+    }
+
+    /// Returns the 64-bit hash of this key, see [`KeyRef::key_hash`].
+    pub fn key_hash(&self) -> u64 {
+        self.as_key_ref().key_hash()
+    }
+}
+
+impl PartialEq for HandleKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_key_ref() == other.as_key_ref()
+    }
+}
+
+impl Eq for HandleKey {}
+
+impl Hash for HandleKey {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.as_key_ref().hash(state);
+    }
+}
+
+/// A borrowed handle key.
+///
+/// Like [`HandleKey`](enum@HandleKey), matches on `KeyRef` need a wildcard arm.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum KeyRef<'a> {
+    Str(&'a str),
+    Number(usize),
+    U64(u64),
+    U128(u128),
+    I64(i64),
+    #[cfg(feature = "uuid")]
+    Uuid(uuid::Uuid),
+    Composite(&'a [HandleKey]),
+    Labeled(&'a LabeledKey),
+    Interned(Symbol),
+    Static(StaticKey),
+    Generational {
+        index: usize,
+        generation: u32,
+    },
+}
+
+impl<'a> KeyRef<'a> {
+    /// Returns the name of this key, if it is a string key.
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self {
+            KeyRef::Str(s) => Some(s),
+            KeyRef::Interned(sym) => Some(sym.as_str()),
+            KeyRef::Static(key) => Some(key.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of this key, if it is an integer key.
+    pub(crate) fn as_integer(&self) -> Option<Integer> {
+        match *self {
+            KeyRef::Number(n) => Some(Integer::NonNegative(n as u128)),
+            KeyRef::U64(n) => Some(Integer::NonNegative(n as u128)),
+            KeyRef::U128(n) => Some(Integer::NonNegative(n)),
+            KeyRef::I64(n) if n < 0 => Some(Integer::Negative(n)),
+            KeyRef::I64(n) => Some(Integer::NonNegative(n as u128)),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for KeyRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (KeyRef::Interned(a), KeyRef::Interned(b)) => a == b,
+            (KeyRef::Static(a), KeyRef::Static(b)) => a == b,
+            (a, b) if a.as_str().is_some() => a.as_str() == b.as_str(),
+            (a, b) if a.as_integer().is_some() => a.as_integer() == b.as_integer(),
+            #[cfg(feature = "uuid")]
+            (KeyRef::Uuid(a), KeyRef::Uuid(b)) => a == b,
+            (KeyRef::Composite(a), KeyRef::Composite(b)) => a == b,
+            (KeyRef::Labeled(a), KeyRef::Labeled(b)) => a == b,
+            (
+                KeyRef::Generational { index, generation },
+                KeyRef::Generational {
+                    index: other_index,
+                    generation: other_generation,
+                },
+            ) => index == other_index && generation == other_generation,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for KeyRef<'_> {}
+
+/// The value of an integer key.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Integer {
+    Negative(i64),
+    NonNegative(u128),
+}
+
+impl Hash for KeyRef<'_> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write_u64(self.key_hash());
+    }
+}
+
+/// Anything that can be viewed as a [`KeyRef`].
+///
+/// `HandleKey` and `Handle<T>` implement `Borrow<dyn AsKeyRef>`, which lets
+/// maps keyed by them be queried with a plain `&str` or `usize` without
+/// allocating a `HandleKey`, e.g. `map.get(&"grass" as &dyn AsKeyRef)`.
+pub trait AsKeyRef {
+    fn as_key_ref(&self) -> KeyRef<'_>;
+}
+
+impl AsKeyRef for KeyRef<'_> {
+    fn as_key_ref(&self) -> KeyRef<'_> {
+        *self
+    }
+}
+
+impl AsKeyRef for HandleKey {
+    fn as_key_ref(&self) -> KeyRef<'_> {
+        HandleKey::as_key_ref(self)
+    }
+}
+
+impl AsKeyRef for &str {
+    fn as_key_ref(&self) -> KeyRef<'_> {
+        KeyRef::Str(self)
+    }
+}
+
+impl AsKeyRef for String {
+    fn as_key_ref(&self) -> KeyRef<'_> {
+        KeyRef::Str(self)
+    }
+}
+
+impl AsKeyRef for Symbol {
+    fn as_key_ref(&self) -> KeyRef<'_> {
+        KeyRef::Interned(*self)
+    }
+}
+
+impl AsKeyRef for StaticKey {
+    fn as_key_ref(&self) -> KeyRef<'_> {
+        KeyRef::Static(*self)
+    }
+}
+
+impl AsKeyRef for usize {
+    fn as_key_ref(&self) -> KeyRef<'_> {
+        KeyRef::Number(*self)
+    }
+}
+
+impl AsKeyRef for u64 {
+    fn as_key_ref(&self) -> KeyRef<'_> {
+        KeyRef::U64(*self)
+    }
+}
+
+impl AsKeyRef for u128 {
+    fn as_key_ref(&self) -> KeyRef<'_> {
+        KeyRef::U128(*self)
+    }
+}
+
+impl AsKeyRef for i64 {
+    fn as_key_ref(&self) -> KeyRef<'_> {
+        KeyRef::I64(*self)
+    }
+}
+
+impl PartialEq for dyn AsKeyRef + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_key_ref() == other.as_key_ref()
+    }
+}
+
+impl Eq for dyn AsKeyRef + '_ {}
+
+impl Hash for dyn AsKeyRef + '_ {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.as_key_ref().hash(state);
+    }
+}
+
+impl PartialOrd for dyn AsKeyRef + '_ {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for dyn AsKeyRef + '_ {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_key_ref().cmp(&other.as_key_ref())
+    }
+}
+
+impl<'a> Borrow<dyn AsKeyRef + 'a> for HandleKey {
+    fn borrow(&self) -> &(dyn AsKeyRef + 'a) {
+        self
+    }
 }
 
 impl From<String> for HandleKey {
@@ -27,34 +358,115 @@ impl From<usize> for HandleKey {
     }
 }
 
+impl From<u64> for HandleKey {
+    fn from(k: u64) -> Self {
+        HandleKey::U64(k)
+    }
+}
+
+impl From<u128> for HandleKey {
+    fn from(k: u128) -> Self {
+        HandleKey::U128(k)
+    }
+}
+
+impl From<i64> for HandleKey {
+    fn from(k: i64) -> Self {
+        HandleKey::I64(k)
+    }
+}
+
+/// Unsuffixed integer literals, as in `Handle::new(5)`, become `Number` keys
+/// when they are non-negative and `I64` keys otherwise.
+impl From<i32> for HandleKey {
+    fn from(k: i32) -> Self {
+        usize::try_from(k).map_or(HandleKey::I64(k.into()), HandleKey::Number)
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl From<uuid::Uuid> for HandleKey {
+    fn from(uuid: uuid::Uuid) -> Self {
+        HandleKey::Uuid(uuid)
+    }
+}
+
+impl<A, B> From<(A, B)> for HandleKey
+where
+    HandleKey: From<A> + From<B>,
+{
+    fn from((a, b): (A, B)) -> Self {
+        HandleKey::Composite(Arc::new([HandleKey::from(a), HandleKey::from(b)]))
+    }
+}
+
+impl<A, B, C> From<(A, B, C)> for HandleKey
+where
+    HandleKey: From<A> + From<B> + From<C>,
+{
+    fn from((a, b, c): (A, B, C)) -> Self {
+        HandleKey::Composite(Arc::new([
+            HandleKey::from(a),
+            HandleKey::from(b),
+            HandleKey::from(c),
+        ]))
+    }
+}
+
+impl From<StaticKey> for HandleKey {
+    fn from(key: StaticKey) -> Self {
+        HandleKey::Static(key)
+    }
+}
+
+impl From<Symbol> for HandleKey {
+    fn from(sym: Symbol) -> Self {
+        HandleKey::Interned(sym)
+    }
+}
+
 impl From<&str> for HandleKey {
     fn from(s: &str) -> Self {
         HandleKey::from(s.to_string())
     }
 }
 
+/// A type that can be used as the key of a [`Handle`].
+///
+/// Implemented for every type with the required traits, so compact key types
+/// like `u32` can be used in place of [`HandleKey`](enum@HandleKey).
+pub trait Key: Clone + Eq + Hash + std::fmt::Debug {}
+
+impl<K: Clone + Eq + Hash + std::fmt::Debug> Key for K {}
+
 /// A typed asset handle.
-pub struct Handle<T> {
+///
+/// A handle only holds a key and a reference count, so it is `Send`, `Sync`
+/// and `Unpin` regardless of `T`.
+///
+/// Handles are keyed by [`HandleKey`](enum@HandleKey) unless another [`Key`]
+/// type is given.
+pub struct Handle<T, K = HandleKey> {
     // Underlying key used for comparison
-    pub key: HandleKey,
+    key: K,
     // Used to count how many things own a clone of the handle.
-    pub count: Option<Arc<()>>,
-    _phantom: PhantomData<T>,
+    count: Option<Arc<RefCount>>,
+    _phantom: PhantomData<fn() -> T>,
 }
 
-impl<T> std::fmt::Debug for Handle<T> {
+impl<T, K: std::fmt::Debug> std::fmt::Debug for Handle<T, K> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct(&format!("Handle<{}>", std::any::type_name::<T>()))
             .field("key", &self.key)
             .field(
                 "references",
-                &format!("{:?}", self.count.as_ref().map(|c| Arc::strong_count(c))),
+                &format!("{:?}", self.count.as_ref().map(Arc::strong_count)),
             )
             .finish()
     }
 }
 
-impl<T> Clone for Handle<T> {
+impl<T, K: Clone> Clone for Handle<T, K> {
     fn clone(&self) -> Self {
         Handle {
             key: self.key.clone(),
@@ -64,20 +476,36 @@ impl<T> Clone for Handle<T> {
     }
 }
 
-impl<T> Hash for Handle<T> {
+impl<T, K: Hash> Hash for Handle<T, K> {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.key.hash(state);
     }
 }
 
-impl<T> PartialEq for Handle<T> {
+impl<'a, T> Borrow<dyn AsKeyRef + 'a> for Handle<T> {
+    fn borrow(&self) -> &(dyn AsKeyRef + 'a) {
+        &self.key
+    }
+}
+
+impl<T, K: PartialEq> PartialEq for Handle<T, K> {
     fn eq(&self, other: &Self) -> bool {
         self.key == other.key
     }
 }
 
-impl<T> Eq for Handle<T> {
-    fn assert_receiver_is_total_eq(&self) {}
+impl<T, K: Eq> Eq for Handle<T, K> {}
+
+impl<T, K: PartialOrd> PartialOrd for Handle<T, K> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.key.partial_cmp(&other.key)
+    }
+}
+
+impl<T, K: Ord> Ord for Handle<T, K> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key.cmp(&other.key)
+    }
 }
 
 impl<T> Handle<T> {
@@ -87,16 +515,244 @@ impl<T> Handle<T> {
     {
         Handle {
             key: HandleKey::from(k),
-            count: Some(Arc::new(())),
+            count: Some(Arc::default()),
             _phantom: PhantomData,
         }
     }
 
+    /// Create a handle that does not count its references.
+    ///
+    /// Entries keyed by untracked handles are never collected from stores.
+    pub fn untracked<K>(k: K) -> Self
+    where
+        HandleKey: From<K>,
+    {
+        Handle::with_key_untracked(HandleKey::from(k))
+    }
+
+    /// Create an untracked handle to a static name.
+    ///
+    /// The name's [`fingerprint`] is computed when the handle is created, so
+    /// in a `const` it is computed at compile time and comparing two static
+    /// handles is a single integer comparison. Hashing is not precomputed:
+    /// like any other string key, the name is hashed on every lookup, so that
+    /// a static handle finds the same entries as a string handle with the
+    /// same name.
     pub const fn from_static(key: &'static str) -> Handle<T> {
         Handle {
-            key: HandleKey::Str(key),
+            key: HandleKey::Static(StaticKey::new(key)),
+            count: None,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T, K> Handle<T, K> {
+    /// Create a tracked handle with a key of any [`Key`] type.
+    pub fn with_key(key: K) -> Self {
+        Handle {
+            key,
+            count: Some(Arc::default()),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Create an untracked handle with a key of any [`Key`] type.
+    pub fn with_key_untracked(key: K) -> Self {
+        Handle {
+            key,
             count: None,
             _phantom: PhantomData,
         }
     }
+
+    /// Returns the key this handle is compared and hashed by.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Consumes the handle, returning its key.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Returns whether this handle counts its references.
+    ///
+    /// Untracked handles, like those created by [`Handle::from_static`], are
+    /// never collected from stores.
+    pub fn is_tracked(&self) -> bool {
+        self.count.is_some()
+    }
+
+    /// Returns the number of strong handles, or `None` if the handle is
+    /// untracked.
+    pub fn strong_count(&self) -> Option<usize> {
+        self.count.as_ref().map(Arc::strong_count)
+    }
+
+    /// Turn this into a handle to a `U` with the same key.
+    ///
+    /// This is for keys that legitimately refer to related values, like the
+    /// `Handle<GpuTexture>` uploaded from a `Handle<Image>`. The new handle
+    /// keeps this handle's reference count.
+    pub fn retype<U>(self) -> Handle<U, K> {
+        Handle {
+            key: self.key,
+            count: self.count,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns whether this is the only remaining clone of a tracked handle.
+    ///
+    /// Untracked handles always return `false`.
+    pub(crate) fn is_unreferenced(&self) -> bool {
+        self.count
+            .as_ref()
+            .is_some_and(|count| Arc::strong_count(count) == 1)
+    }
+
+    /// Moves the count of a tracked handle into a new allocation if this is
+    /// its only remaining clone, or gives the handle back if it is not.
+    ///
+    /// Unlike [`Handle::is_unreferenced`], this can't race with
+    /// [`WeakHandle::upgrade`]: once it succeeds, weak handles to the old
+    /// count can no longer be upgraded.
+    pub(crate) fn try_into_unreferenced(self) -> Result<Self, Self> {
+        let Handle { key, count, .. } = self;
+        let (count, unreferenced) = match count.map(Arc::try_unwrap) {
+            Some(Ok(count)) => (Some(Arc::new(count)), true),
+            Some(Err(count)) => (Some(count), false),
+            None => (None, false),
+        };
+        let handle = Handle {
+            key,
+            count,
+            _phantom: PhantomData,
+        };
+        if unreferenced {
+            Ok(handle)
+        } else {
+            Err(handle)
+        }
+    }
+}
+
+/// Sorts entries taken out of a store into the ones whose handles are
+/// unreferenced, which are returned, and the rest, which are handed to
+/// `restore` to be put back.
+///
+/// Every store's `collect_unreferenced` goes through this, so that evicting an
+/// entry always goes through [`Handle::try_into_unreferenced`] and weak
+/// handles to it can't be upgraded afterwards. The candidates are expected to
+/// be the only clones of their handles the store holds.
+pub(crate) fn collect_unreferenced<T, K, V>(
+    candidates: impl IntoIterator<Item = (Handle<T, K>, V)>,
+    mut restore: impl FnMut(Handle<T, K>, V),
+) -> Vec<(Handle<T, K>, V)> {
+    let mut collected = vec![];
+    for (handle, value) in candidates {
+        match handle.try_into_unreferenced() {
+            Ok(handle) => collected.push((handle, value)),
+            Err(handle) => restore(handle, value),
+        }
+    }
+    collected
+}
+
+impl<T, K: Clone> Handle<T, K> {
+    /// Returns a handle to a `U` with the same key, see [`Handle::retype`].
+    ///
+    /// The new handle has a reference count of its own, and keeps this
+    /// handle referenced for as long as it exists. So when both handles are
+    /// kept in stores, the cast handle can be collected first, and then this
+    /// one.
+    pub fn cast<U>(&self) -> Handle<U, K> {
+        Handle {
+            key: self.key.clone(),
+            count: RefCount::derived(&self.count),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+// Handles don't own a `T`, so none of these may depend on `T`.
+const _: () = {
+    fn assert_send_sync_unpin<X: Send + Sync + Unpin>() {}
+
+    #[allow(dead_code)]
+    fn assert_handles_are_send_sync_unpin<T>() {
+        assert_send_sync_unpin::<Handle<T>>();
+        assert_send_sync_unpin::<Handle<T, u32>>();
+        assert_send_sync_unpin::<WeakHandle<T>>();
+        assert_send_sync_unpin::<WeakHandle<T, u32>>();
+        assert_send_sync_unpin::<HashedHandle<T>>();
+        assert_send_sync_unpin::<UntypedHandle>();
+        assert_send_sync_unpin::<HandleAllocator<T>>();
+        assert_send_sync_unpin::<AtomicHandleAllocator<T>>();
+    }
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Tex;
+
+    #[test]
+    fn cast_handles_keep_the_original_referenced() {
+        struct Image;
+        struct GpuTexture;
+
+        let mut images = HandleMap::new();
+        let mut textures = HandleMap::new();
+        {
+            let image = Handle::new("grass.png");
+            let texture = image.cast::<GpuTexture>();
+            assert_eq!(texture.key(), image.key());
+            assert_eq!(image.strong_count(), Some(2));
+            textures.insert(texture, GpuTexture);
+            images.insert(image, Image);
+        }
+        assert!(images.collect_unreferenced().is_empty());
+        assert_eq!(textures.collect_unreferenced().len(), 1);
+        assert_eq!(images.collect_unreferenced().len(), 1);
+    }
+
+    #[test]
+    fn retype_keeps_the_count() {
+        struct Image;
+        struct GpuTexture;
+
+        let image = Handle::<Image>::new("grass.png");
+        let weak = image.downgrade();
+        let texture = image.retype::<GpuTexture>();
+        assert_eq!(texture.strong_count(), Some(1));
+        assert_eq!(weak.strong_count(), Some(1));
+    }
+
+    #[test]
+    fn integer_literals_are_number_keys() {
+        assert!(matches!(Handle::<Tex>::new(5).key(), HandleKey::Number(5)));
+        assert!(matches!(Handle::<Tex>::new(-5).key(), HandleKey::I64(-5)));
+    }
+
+    #[test]
+    fn integer_keys_compare_by_value() {
+        let keys = [
+            HandleKey::Number(5),
+            HandleKey::U64(5),
+            HandleKey::U128(5),
+            HandleKey::I64(5),
+            HandleKey::from(5),
+        ];
+        for a in &keys {
+            for b in &keys {
+                assert_eq!(a, b);
+                assert_eq!(a.key_hash(), b.key_hash());
+            }
+        }
+        assert_ne!(HandleKey::I64(-5), HandleKey::from(5u64));
+        assert_ne!(HandleKey::from(5), HandleKey::from("5"));
+    }
 }
@@ -0,0 +1,70 @@
+//! Macros for declaring static handles.
+
+/// Declare typed static handles.
+///
+/// Each identifier becomes a constant `Handle<T>` named after the identifier
+/// in lowercase, unless a name is given explicitly. Using the same name twice
+/// in one invocation is a compile error.
+///
+/// ```
+/// # struct Texture;
+/// # struct Mesh;
+/// handle_key::handles! {
+///     pub Texture { GRASS, STONE, WATER = "textures/water.png" }
+///     Mesh { PLAYER }
+/// }
+///
+/// assert_eq!(GRASS.key().as_str(), Some("grass"));
+/// assert_eq!(WATER.key().as_str(), Some("textures/water.png"));
+/// ```
+///
+/// Names are compared after lowercasing, so these two handles clash:
+///
+/// ```compile_fail
+/// handle_key::handles! {
+///     u8 { GRASS, Grass }
+/// }
+/// ```
+///
+/// As do explicit names that repeat a generated one, even across types:
+///
+/// ```compile_fail
+/// handle_key::handles! {
+///     u8 { GRASS }
+///     u16 { TURF = "grass" }
+/// }
+/// ```
+#[macro_export]
+macro_rules! handles {
+    ($(
+        $vis:vis $ty:ty {
+            $( $(#[$meta:meta])* $name:ident $(= $key:literal)? ),* $(,)?
+        }
+    )*) => {
+        $($(
+            $(#[$meta])*
+            $vis const $name: $crate::Handle<$ty> =
+                $crate::Handle::from_static($crate::__handle_name!($name $(= $key)?));
+        )*)*
+
+        const _: () = $crate::__private::assert_unique_names(&[
+            $($( $crate::__handle_name!($name $(= $key)?), )*)*
+        ]);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __handle_name {
+    ($name:ident = $key:literal) => {
+        $key
+    };
+    ($name:ident) => {{
+        const NAME: &str = stringify!($name);
+        const BYTES: [u8; NAME.len()] = $crate::__private::ascii_lowercase(NAME);
+        match ::core::str::from_utf8(&BYTES) {
+            Ok(name) => name,
+            Err(_) => panic!("handle names must be valid UTF-8"),
+        }
+    }};
+}
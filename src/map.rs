@@ -0,0 +1,357 @@
+//! A map of values keyed by typed handles.
+use std::{
+    collections::{hash_map, HashMap},
+    hash::{BuildHasher, RandomState},
+};
+
+use crate::{AsKeyRef, Handle, HandleKey, Key, KeyRef};
+
+pub use std::collections::hash_map::Entry;
+
+/// A map from `Handle<T>` to `T`.
+///
+/// Because the map is keyed by `Handle<T>` a `Handle<Texture>` can only ever
+/// be used to look up a `Texture`.
+///
+/// Like `Handle`, the map is keyed by [`HandleKey`](enum@HandleKey) unless
+/// another [`Key`] type is given, and like `HashMap` it can use a custom hasher, such as
+/// [`BuildKeyHasher`](crate::BuildKeyHasher). Maps with other key or hasher
+/// types are created with `default` or `with_hasher`.
+pub struct HandleMap<T, K = HandleKey, S = RandomState> {
+    inner: HashMap<Handle<T, K>, T, S>,
+}
+
+impl<T, K, S: Default> Default for HandleMap<T, K, S> {
+    fn default() -> Self {
+        HandleMap {
+            inner: HashMap::default(),
+        }
+    }
+}
+
+impl<T: std::fmt::Debug, K: std::fmt::Debug, S> std::fmt::Debug for HandleMap<T, K, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.inner.iter()).finish()
+    }
+}
+
+impl<T: Clone, K: Clone, S: Clone> Clone for HandleMap<T, K, S> {
+    fn clone(&self) -> Self {
+        HandleMap {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> HandleMap<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        HandleMap {
+            inner: HashMap::with_capacity(capacity),
+        }
+    }
+}
+
+impl<T, K, S> HandleMap<T, K, S> {
+    pub fn with_hasher(hasher: S) -> Self {
+        HandleMap {
+            inner: HashMap::with_hasher(hasher),
+        }
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        HandleMap {
+            inner: HashMap::with_capacity_and_hasher(capacity, hasher),
+        }
+    }
+
+    pub fn hasher(&self) -> &S {
+        self.inner.hasher()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    pub fn retain(&mut self, f: impl FnMut(&Handle<T, K>, &mut T) -> bool) {
+        self.inner.retain(f);
+    }
+
+    pub fn iter(&self) -> hash_map::Iter<'_, Handle<T, K>, T> {
+        self.inner.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> hash_map::IterMut<'_, Handle<T, K>, T> {
+        self.inner.iter_mut()
+    }
+
+    pub fn handles(&self) -> hash_map::Keys<'_, Handle<T, K>, T> {
+        self.inner.keys()
+    }
+
+    pub fn values(&self) -> hash_map::Values<'_, Handle<T, K>, T> {
+        self.inner.values()
+    }
+
+    pub fn values_mut(&mut self) -> hash_map::ValuesMut<'_, Handle<T, K>, T> {
+        self.inner.values_mut()
+    }
+
+    pub fn drain(&mut self) -> hash_map::Drain<'_, Handle<T, K>, T> {
+        self.inner.drain()
+    }
+}
+
+impl<T, K: Key, S: BuildHasher> HandleMap<T, K, S> {
+    /// Insert a value, returning the handle and value previously stored under
+    /// the same key.
+    ///
+    /// The map keeps the given handle as its key, so the entry's references
+    /// are counted through the handle that was given last. The displaced
+    /// handle is handed back rather than dropped, leaving it to the caller to
+    /// decide when it goes away.
+    pub fn insert(&mut self, handle: Handle<T, K>, value: T) -> Option<(Handle<T, K>, T)> {
+        let previous = self.inner.remove_entry(&handle);
+        self.inner.insert(handle, value);
+        previous
+    }
+
+    pub fn contains(&self, handle: &Handle<T, K>) -> bool {
+        self.inner.contains_key(handle)
+    }
+
+    pub fn get(&self, handle: &Handle<T, K>) -> Option<&T> {
+        self.inner.get(handle)
+    }
+
+    pub fn get_mut(&mut self, handle: &Handle<T, K>) -> Option<&mut T> {
+        self.inner.get_mut(handle)
+    }
+
+    pub fn remove(&mut self, handle: &Handle<T, K>) -> Option<T> {
+        self.inner.remove(handle)
+    }
+
+    /// Remove an entry, returning the stored handle along with its value.
+    pub fn remove_entry(&mut self, handle: &Handle<T, K>) -> Option<(Handle<T, K>, T)> {
+        self.inner.remove_entry(handle)
+    }
+
+    /// Get the entry for a handle.
+    ///
+    /// An occupied entry keeps the handle it was stored with; the given handle
+    /// is only stored if the entry is vacant.
+    pub fn entry(&mut self, handle: Handle<T, K>) -> Entry<'_, Handle<T, K>, T> {
+        self.inner.entry(handle)
+    }
+
+    /// Remove every entry whose handle is no longer held by anything outside
+    /// of this map, returning the evicted handles and values.
+    ///
+    /// Entries keyed by untracked handles (see [`Handle::from_static`]) are
+    /// never collected. Handles derived from another, like sub-handles, keep
+    /// it referenced, so it is only collected once they have been. Weak
+    /// handles to an evicted entry can no longer be upgraded.
+    pub fn collect_unreferenced(&mut self) -> Vec<(Handle<T, K>, T)> {
+        let candidates = self
+            .inner
+            .extract_if(|handle, _| handle.is_unreferenced())
+            .collect::<Vec<_>>();
+        crate::collect_unreferenced(candidates, |handle, value| {
+            self.inner.insert(handle, value);
+        })
+    }
+}
+
+impl<T, S: BuildHasher> HandleMap<T, HandleKey, S> {
+    /// Look up a value by the name of its handle.
+    pub fn get_named(&self, name: &str) -> Option<&T> {
+        self.inner.get(&KeyRef::Str(name) as &dyn AsKeyRef)
+    }
+
+    pub fn get_named_mut(&mut self, name: &str) -> Option<&mut T> {
+        self.inner.get_mut(&KeyRef::Str(name) as &dyn AsKeyRef)
+    }
+
+    /// Returns the stored handle with the given name, if any.
+    pub fn handle_named(&self, name: &str) -> Option<&Handle<T>> {
+        self.inner
+            .get_key_value(&KeyRef::Str(name) as &dyn AsKeyRef)
+            .map(|(handle, _)| handle)
+    }
+
+    pub fn contains_named(&self, name: &str) -> bool {
+        self.inner.contains_key(&KeyRef::Str(name) as &dyn AsKeyRef)
+    }
+
+    pub fn remove_named(&mut self, name: &str) -> Option<T> {
+        self.inner.remove(&KeyRef::Str(name) as &dyn AsKeyRef)
+    }
+
+    pub(crate) fn contains_key(&self, key: &HandleKey) -> bool {
+        self.inner.contains_key(key as &dyn AsKeyRef)
+    }
+}
+
+impl<T, K: Key, S: BuildHasher> std::ops::Index<&Handle<T, K>> for HandleMap<T, K, S> {
+    type Output = T;
+
+    fn index(&self, handle: &Handle<T, K>) -> &T {
+        self.get(handle)
+            .unwrap_or_else(|| panic!("no entry for {handle:?}"))
+    }
+}
+
+impl<T, K: Key, S: BuildHasher + Default> FromIterator<(Handle<T, K>, T)> for HandleMap<T, K, S> {
+    fn from_iter<I: IntoIterator<Item = (Handle<T, K>, T)>>(iter: I) -> Self {
+        let mut map = HandleMap::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<T, K: Key, S: BuildHasher> Extend<(Handle<T, K>, T)> for HandleMap<T, K, S> {
+    fn extend<I: IntoIterator<Item = (Handle<T, K>, T)>>(&mut self, iter: I) {
+        for (handle, value) in iter {
+            self.insert(handle, value);
+        }
+    }
+}
+
+impl<T, K, S> IntoIterator for HandleMap<T, K, S> {
+    type Item = (Handle<T, K>, T);
+    type IntoIter = hash_map::IntoIter<Handle<T, K>, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
+    }
+}
+
+impl<'a, T, K, S> IntoIterator for &'a HandleMap<T, K, S> {
+    type Item = (&'a Handle<T, K>, &'a T);
+    type IntoIter = hash_map::Iter<'a, Handle<T, K>, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.iter()
+    }
+}
+
+impl<'a, T, K, S> IntoIterator for &'a mut HandleMap<T, K, S> {
+    type Item = (&'a Handle<T, K>, &'a mut T);
+    type IntoIter = hash_map::IterMut<'a, Handle<T, K>, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_keeps_the_latest_handle() {
+        let mut map = HandleMap::<u32>::new();
+        map.insert(Handle::new("tex"), 1);
+        let tex = Handle::new("tex");
+        let (previous, value) = map.insert(tex.clone(), 2).unwrap();
+        assert_eq!((previous.strong_count(), value), (Some(1), 1));
+        assert_eq!(tex.strong_count(), Some(2));
+        assert!(map.collect_unreferenced().is_empty());
+        assert_eq!(map.get(&tex), Some(&2));
+    }
+
+    #[test]
+    fn entry_keeps_the_stored_handle() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut map = HandleMap::<u32>::new();
+        map.insert(Handle::with_drop_sender("tex", sender), 1);
+        *map.entry(Handle::new("tex")).or_insert(0) += 1;
+        assert!(receiver.try_recv().is_err());
+        assert_eq!(map[&Handle::new("tex")], 2);
+        assert_eq!(map.collect_unreferenced().len(), 1);
+        assert_eq!(receiver.try_recv(), Ok(HandleKey::from("tex")));
+    }
+
+    #[test]
+    fn collect_unreferenced_evicts_unheld_entries() {
+        let mut map = HandleMap::<u32>::new();
+        map.insert(Handle::new("a"), 1);
+        map.insert(Handle::new(1usize), 2);
+        let mut evicted = map.collect_unreferenced();
+        evicted.sort_by_key(|(_, value)| *value);
+        assert_eq!(evicted.len(), 2);
+        assert_eq!(evicted[0].0, Handle::new("a"));
+        assert_eq!(evicted[1].1, 2);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn collect_unreferenced_keeps_held_entries() {
+        let mut map = HandleMap::<u32>::new();
+        let held = Handle::new("held");
+        map.insert(held.clone(), 1);
+        map.insert(Handle::new("dropped"), 2);
+        let evicted = map.collect_unreferenced();
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].1, 2);
+        assert_eq!(map.get(&held), Some(&1));
+
+        drop(held);
+        assert_eq!(map.collect_unreferenced().len(), 1);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn collect_unreferenced_never_collects_untracked_handles() {
+        let mut map = HandleMap::<u32>::new();
+        map.insert(Handle::from_static("static"), 1);
+        map.insert(Handle::untracked(7usize), 2);
+        assert!(map.collect_unreferenced().is_empty());
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn collect_unreferenced_after_reinserting() {
+        let mut map = HandleMap::<u32>::new();
+        let first = Handle::new("tex");
+        map.insert(first.clone(), 1);
+        let second = Handle::new("tex");
+        map.insert(second.clone(), 2);
+
+        // Only the handle given last keeps the entry alive.
+        drop(second);
+        let evicted = map.collect_unreferenced();
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].1, 2);
+        assert!(!map.contains(&first));
+    }
+
+    #[test]
+    fn maps_with_other_key_types() {
+        let mut map = HandleMap::<&str, u32>::default();
+        let held = Handle::with_key(1);
+        map.insert(held.clone(), "held");
+        map.insert(Handle::with_key(2), "dropped");
+        map.insert(Handle::with_key_untracked(3), "untracked");
+        assert_eq!(map.get(&Handle::with_key(1)), Some(&"held"));
+        assert_eq!(map[&Handle::with_key(3)], "untracked");
+
+        let evicted = map.collect_unreferenced();
+        assert_eq!(evicted.len(), 1);
+        assert_eq!((*evicted[0].0.key(), evicted[0].1), (2, "dropped"));
+        assert_eq!(map.len(), 2);
+        assert!(map.contains(&held));
+    }
+}
@@ -0,0 +1,354 @@
+//! An ordered map of values keyed by typed handles.
+use std::{
+    cmp::Ordering,
+    collections::{btree_map, BTreeMap},
+};
+
+use crate::{AsKeyRef, Handle, HandleKey, KeyRef};
+
+impl KeyRef<'_> {
+    /// Ranks the kinds of keys: strings, then integers, generational keys,
+    /// UUIDs, composite keys and finally labeled keys.
+    fn rank(&self) -> u8 {
+        match self {
+            KeyRef::Str(_) | KeyRef::Interned(_) | KeyRef::Static(_) => 0,
+            KeyRef::Number(_) | KeyRef::U64(_) | KeyRef::U128(_) | KeyRef::I64(_) => 1,
+            KeyRef::Generational { .. } => 2,
+            #[cfg(feature = "uuid")]
+            KeyRef::Uuid(_) => 3,
+            KeyRef::Composite(_) => 4,
+            KeyRef::Labeled(_) => 5,
+        }
+    }
+}
+
+/// Keys are ordered first by kind (strings, integers, generational keys,
+/// UUIDs, composite keys, labeled keys) and then by value. This agrees with
+/// equality: string keys compare by text and integer keys by value, whatever
+/// their variant.
+impl PartialOrd for KeyRef<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for KeyRef<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self == other {
+            return Ordering::Equal;
+        }
+        self.rank()
+            .cmp(&other.rank())
+            .then_with(|| match (self, other) {
+                (
+                    KeyRef::Generational { index, generation },
+                    KeyRef::Generational {
+                        index: other_index,
+                        generation: other_generation,
+                    },
+                ) => (index, generation).cmp(&(other_index, other_generation)),
+                #[cfg(feature = "uuid")]
+                (KeyRef::Uuid(a), KeyRef::Uuid(b)) => a.cmp(b),
+                (KeyRef::Composite(a), KeyRef::Composite(b)) => a.cmp(b),
+                (KeyRef::Labeled(a), KeyRef::Labeled(b)) => a
+                    .base()
+                    .cmp(b.base())
+                    .then_with(|| a.label().cmp(b.label())),
+                (a, b) => a
+                    .as_str()
+                    .cmp(&b.as_str())
+                    .then_with(|| a.as_integer().cmp(&b.as_integer())),
+            })
+    }
+}
+
+impl PartialOrd for HandleKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HandleKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_key_ref().cmp(&other.as_key_ref())
+    }
+}
+
+/// A map from `Handle<T>` to `T` that iterates in key order.
+///
+/// Useful wherever output has to be deterministic, like save files, network
+/// replication or reproducible builds.
+pub struct OrderedHandleMap<T, K = HandleKey> {
+    inner: BTreeMap<Handle<T, K>, T>,
+}
+
+impl<T, K> Default for OrderedHandleMap<T, K> {
+    fn default() -> Self {
+        OrderedHandleMap {
+            inner: BTreeMap::default(),
+        }
+    }
+}
+
+impl<T: std::fmt::Debug, K: std::fmt::Debug> std::fmt::Debug for OrderedHandleMap<T, K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.inner.iter()).finish()
+    }
+}
+
+impl<T: Clone, K: Clone> Clone for OrderedHandleMap<T, K> {
+    fn clone(&self) -> Self {
+        OrderedHandleMap {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> OrderedHandleMap<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T, K> OrderedHandleMap<T, K> {
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    pub fn iter(&self) -> btree_map::Iter<'_, Handle<T, K>, T> {
+        self.inner.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> btree_map::IterMut<'_, Handle<T, K>, T> {
+        self.inner.iter_mut()
+    }
+
+    pub fn handles(&self) -> btree_map::Keys<'_, Handle<T, K>, T> {
+        self.inner.keys()
+    }
+
+    pub fn values(&self) -> btree_map::Values<'_, Handle<T, K>, T> {
+        self.inner.values()
+    }
+
+    pub fn values_mut(&mut self) -> btree_map::ValuesMut<'_, Handle<T, K>, T> {
+        self.inner.values_mut()
+    }
+}
+
+impl<T, K: Ord> OrderedHandleMap<T, K> {
+    /// Insert a value, returning the handle and value previously stored under
+    /// the same key.
+    ///
+    /// As with [`HandleMap::insert`](crate::HandleMap::insert), the map keeps
+    /// the given handle as its key and hands the displaced one back.
+    pub fn insert(&mut self, handle: Handle<T, K>, value: T) -> Option<(Handle<T, K>, T)> {
+        let previous = self.inner.remove_entry(&handle);
+        self.inner.insert(handle, value);
+        previous
+    }
+
+    pub fn contains(&self, handle: &Handle<T, K>) -> bool {
+        self.inner.contains_key(handle)
+    }
+
+    pub fn get(&self, handle: &Handle<T, K>) -> Option<&T> {
+        self.inner.get(handle)
+    }
+
+    pub fn get_mut(&mut self, handle: &Handle<T, K>) -> Option<&mut T> {
+        self.inner.get_mut(handle)
+    }
+
+    pub fn remove(&mut self, handle: &Handle<T, K>) -> Option<T> {
+        self.inner.remove(handle)
+    }
+
+    /// Remove an entry, returning the stored handle along with its value.
+    pub fn remove_entry(&mut self, handle: &Handle<T, K>) -> Option<(Handle<T, K>, T)> {
+        self.inner.remove_entry(handle)
+    }
+
+    /// Get the entry for a handle.
+    ///
+    /// An occupied entry keeps the handle it was stored with; the given handle
+    /// is only stored if the entry is vacant.
+    pub fn entry(&mut self, handle: Handle<T, K>) -> btree_map::Entry<'_, Handle<T, K>, T> {
+        self.inner.entry(handle)
+    }
+
+    pub fn first(&self) -> Option<(&Handle<T, K>, &T)> {
+        self.inner.first_key_value()
+    }
+
+    pub fn last(&self) -> Option<(&Handle<T, K>, &T)> {
+        self.inner.last_key_value()
+    }
+
+    pub fn retain(&mut self, f: impl FnMut(&Handle<T, K>, &mut T) -> bool) {
+        self.inner.retain(f);
+    }
+
+    /// Remove every entry whose handle is no longer held by anything outside
+    /// of this map, returning the evicted handles and values in key order.
+    ///
+    /// Entries keyed by untracked handles (see [`Handle::from_static`]) are
+    /// never collected, and weak handles to an evicted entry can no longer be
+    /// upgraded.
+    pub fn collect_unreferenced(&mut self) -> Vec<(Handle<T, K>, T)> {
+        let candidates = self
+            .inner
+            .extract_if(.., |handle, _| handle.is_unreferenced())
+            .collect::<Vec<_>>();
+        crate::collect_unreferenced(candidates, |handle, value| {
+            self.inner.insert(handle, value);
+        })
+    }
+}
+
+impl<T> OrderedHandleMap<T> {
+    /// Look up a value by the name of its handle.
+    pub fn get_named(&self, name: &str) -> Option<&T> {
+        self.inner.get(&KeyRef::Str(name) as &dyn AsKeyRef)
+    }
+
+    pub fn get_named_mut(&mut self, name: &str) -> Option<&mut T> {
+        self.inner.get_mut(&KeyRef::Str(name) as &dyn AsKeyRef)
+    }
+
+    pub fn contains_named(&self, name: &str) -> bool {
+        self.inner.contains_key(&KeyRef::Str(name) as &dyn AsKeyRef)
+    }
+
+    pub fn remove_named(&mut self, name: &str) -> Option<T> {
+        self.inner.remove(&KeyRef::Str(name) as &dyn AsKeyRef)
+    }
+}
+
+impl<T, K: Ord + std::fmt::Debug> std::ops::Index<&Handle<T, K>> for OrderedHandleMap<T, K> {
+    type Output = T;
+
+    fn index(&self, handle: &Handle<T, K>) -> &T {
+        self.get(handle)
+            .unwrap_or_else(|| panic!("no entry for {handle:?}"))
+    }
+}
+
+impl<T, K: Ord> FromIterator<(Handle<T, K>, T)> for OrderedHandleMap<T, K> {
+    fn from_iter<I: IntoIterator<Item = (Handle<T, K>, T)>>(iter: I) -> Self {
+        let mut map = OrderedHandleMap::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<T, K: Ord> Extend<(Handle<T, K>, T)> for OrderedHandleMap<T, K> {
+    fn extend<I: IntoIterator<Item = (Handle<T, K>, T)>>(&mut self, iter: I) {
+        for (handle, value) in iter {
+            self.insert(handle, value);
+        }
+    }
+}
+
+impl<T, K> IntoIterator for OrderedHandleMap<T, K> {
+    type Item = (Handle<T, K>, T);
+    type IntoIter = btree_map::IntoIter<Handle<T, K>, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
+    }
+}
+
+impl<'a, T, K> IntoIterator for &'a OrderedHandleMap<T, K> {
+    type Item = (&'a Handle<T, K>, &'a T);
+    type IntoIter = btree_map::Iter<'a, Handle<T, K>, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.iter()
+    }
+}
+
+impl<'a, T, K> IntoIterator for &'a mut OrderedHandleMap<T, K> {
+    type Item = (&'a Handle<T, K>, &'a mut T);
+    type IntoIter = btree_map::IterMut<'a, Handle<T, K>, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_ordered_by_kind_then_value() {
+        let mut keys = vec![
+            HandleKey::from("b"),
+            HandleKey::from(2usize),
+            HandleKey::from(-1i64),
+            HandleKey::interned("a"),
+            HandleKey::Generational {
+                index: 0,
+                generation: 0,
+            },
+            HandleKey::from((1usize, "x")),
+            HandleKey::from("a").with_label("x"),
+            HandleKey::from(u128::MAX),
+        ];
+        keys.sort();
+        assert_eq!(
+            keys,
+            [
+                HandleKey::from("a"),
+                HandleKey::from("b"),
+                HandleKey::from(-1i64),
+                HandleKey::from(2usize),
+                HandleKey::from(u128::MAX),
+                HandleKey::Generational {
+                    index: 0,
+                    generation: 0,
+                },
+                HandleKey::from((1usize, "x")),
+                HandleKey::from("a").with_label("x"),
+            ]
+        );
+        assert_eq!(
+            HandleKey::from(5usize).cmp(&HandleKey::U64(5)),
+            Ordering::Equal
+        );
+        assert_eq!(HandleKey::interned("q").cmp(&"q".into()), Ordering::Equal);
+    }
+
+    #[test]
+    fn iterates_in_key_order() {
+        let map = OrderedHandleMap::<u32>::from_iter([
+            (Handle::new("c"), 3),
+            (Handle::new("a"), 1),
+            (Handle::new("b"), 2),
+        ]);
+        assert_eq!(map.values().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(map.get_named("b"), Some(&2));
+    }
+
+    #[test]
+    fn insert_keeps_the_latest_handle_and_entry_the_stored_one() {
+        let mut map = OrderedHandleMap::<u32>::new();
+        map.insert(Handle::new("tex"), 1);
+        let tex = Handle::new("tex");
+        assert_eq!(map.insert(tex.clone(), 2).map(|(_, value)| value), Some(1));
+        assert!(map.collect_unreferenced().is_empty());
+        *map.entry(Handle::new("tex")).or_insert(0) += 1;
+        assert!(map.collect_unreferenced().is_empty());
+        drop(tex);
+        assert_eq!(map.collect_unreferenced().len(), 1);
+    }
+}
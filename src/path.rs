@@ -0,0 +1,193 @@
+//! Treating string keys as `/`-separated paths.
+use std::hash::BuildHasher;
+
+use crate::{Handle, HandleKey, HandleMap};
+
+/// A string key viewed as a `/`-separated path, like
+/// `"textures/terrain/grass.png"`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyPath<'a> {
+    path: &'a str,
+}
+
+impl<'a> KeyPath<'a> {
+    pub fn new(path: &'a str) -> Self {
+        KeyPath { path }
+    }
+
+    pub fn as_str(&self) -> &'a str {
+        self.path
+    }
+
+    /// Returns the non-empty segments of the path.
+    pub fn segments(&self) -> impl Iterator<Item = &'a str> {
+        self.path.split('/').filter(|segment| !segment.is_empty())
+    }
+
+    /// Returns the path without its last segment, or `None` if the path has
+    /// only one segment.
+    ///
+    /// Like [`KeyPath::segments`], this ignores empty segments, so the parent
+    /// of `"a//b/"` is `"a"` and `"/a"` has no parent.
+    pub fn parent(&self) -> Option<KeyPath<'a>> {
+        let path = self.path.trim_end_matches('/');
+        let (parent, _) = path.rsplit_once('/')?;
+        let parent = KeyPath::new(parent.trim_end_matches('/'));
+        parent.segments().next().is_some().then_some(parent)
+    }
+
+    /// Returns the last segment of the path.
+    pub fn file_name(&self) -> Option<&'a str> {
+        self.segments().last()
+    }
+
+    /// Returns the file name without its extension.
+    pub fn file_stem(&self) -> Option<&'a str> {
+        let name = self.file_name()?;
+        match name.rsplit_once('.') {
+            Some((stem, _)) if !stem.is_empty() => Some(stem),
+            _ => Some(name),
+        }
+    }
+
+    /// Returns the extension of the file name, if any.
+    pub fn extension(&self) -> Option<&'a str> {
+        let name = self.file_name()?;
+        match name.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => Some(extension),
+            _ => None,
+        }
+    }
+
+    /// Returns whether `prefix` is made of the leading segments of this
+    /// path, e.g. `"textures/terrain/"` is a prefix of
+    /// `"textures/terrain/grass.png"` but not of `"textures/terrain2/dirt.png"`.
+    pub fn starts_with(&self, prefix: &str) -> bool {
+        let mut segments = self.segments();
+        KeyPath::new(prefix)
+            .segments()
+            .all(|prefix| segments.next() == Some(prefix))
+    }
+}
+
+impl HandleKey {
+    /// Returns this key as a path, if it is a string key.
+    pub fn path(&self) -> Option<KeyPath<'_>> {
+        self.as_str().map(KeyPath::new)
+    }
+
+    /// Returns whether this is a string key under the path `prefix`, or a
+    /// labeled key whose [`root`](HandleKey::root) is.
+    pub fn is_under(&self, prefix: &str) -> bool {
+        self.root()
+            .path()
+            .is_some_and(|path| path.starts_with(prefix))
+    }
+}
+
+impl<T, S: BuildHasher> HandleMap<T, HandleKey, S> {
+    /// Iterate over the entries whose keys are paths under `prefix`,
+    /// including sub-assets labeled within them.
+    pub fn iter_under<'a>(
+        &'a self,
+        prefix: &'a str,
+    ) -> impl Iterator<Item = (&'a Handle<T>, &'a T)> + 'a {
+        self.iter()
+            .filter(move |(handle, _)| handle.key.is_under(prefix))
+    }
+
+    /// Remove every entry whose key is a path under `prefix`, including
+    /// sub-assets labeled within them, returning the removed handles and
+    /// values.
+    pub fn remove_under(&mut self, prefix: &str) -> Vec<(Handle<T>, T)> {
+        let under = self
+            .iter_under(prefix)
+            .map(|(handle, _)| handle.clone())
+            .collect::<Vec<_>>();
+        under
+            .into_iter()
+            .filter_map(|handle| self.remove_entry(&handle))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parents_skip_empty_segments() {
+        let parent = |path| KeyPath::new(path).parent().map(|parent| parent.as_str());
+        assert_eq!(
+            parent("textures/terrain/grass.png"),
+            Some("textures/terrain")
+        );
+        assert_eq!(parent("/textures/grass.png"), Some("/textures"));
+        assert_eq!(parent("a//b/"), Some("a"));
+        assert_eq!(parent("a"), None);
+        assert_eq!(parent("/a"), None);
+        assert_eq!(parent("a/"), None);
+        assert_eq!(parent(""), None);
+    }
+
+    #[test]
+    fn file_stems_and_extensions() {
+        let path = KeyPath::new("textures/grass.tar.gz");
+        assert_eq!(path.file_name(), Some("grass.tar.gz"));
+        assert_eq!(path.file_stem(), Some("grass.tar"));
+        assert_eq!(path.extension(), Some("gz"));
+
+        let path = KeyPath::new("config/.hidden");
+        assert_eq!(path.file_stem(), Some(".hidden"));
+        assert_eq!(path.extension(), None);
+
+        let path = KeyPath::new("scenes/level/");
+        assert_eq!(path.file_stem(), Some("level"));
+        assert_eq!(path.extension(), None);
+
+        assert_eq!(KeyPath::new("/").file_stem(), None);
+        assert_eq!(KeyPath::new("").extension(), None);
+    }
+
+    #[test]
+    fn prefixes_match_whole_segments() {
+        let path = KeyPath::new("textures/terrain/grass.png");
+        assert!(path.starts_with("textures"));
+        assert!(path.starts_with("textures/terrain/"));
+        assert!(!path.starts_with("textures/terr"));
+        assert!(!HandleKey::from("textures/terrain2/dirt.png").is_under("textures/terrain"));
+        assert!(!HandleKey::from(1usize).is_under(""));
+    }
+
+    #[test]
+    fn labeled_keys_are_under_their_base() {
+        let mesh = HandleKey::from("textures/terrain/m.gltf").with_label("Mesh0");
+        assert!(mesh.is_under("textures/terrain"));
+        assert!(!mesh.is_under("models"));
+    }
+
+    #[test]
+    fn nested_labels_are_under_their_root() {
+        let key = HandleKey::from("a/b.gltf").with_label("x").with_label("y");
+        assert_eq!(key.base(), &HandleKey::from("a/b.gltf").with_label("x"));
+        assert_eq!(key.root(), &HandleKey::from("a/b.gltf"));
+        assert!(key.is_under("a"));
+        assert!(!key.is_under("b"));
+    }
+
+    #[test]
+    fn remove_under_removes_sub_assets() {
+        struct Asset;
+
+        let mut map = HandleMap::<u32>::new();
+        let model = Handle::<Asset>::new("textures/terrain/m.gltf");
+        map.insert(Handle::new("textures/terrain/m.gltf"), 0);
+        let mesh = model.sub_handle("Mesh0");
+        map.insert(mesh.clone(), 1);
+        map.insert(mesh.sub_handle("Primitive1"), 3);
+        map.insert(Handle::new("textures/water.png"), 2);
+        assert_eq!(map.iter_under("textures/terrain").count(), 3);
+        assert_eq!(map.remove_under("textures/terrain").len(), 3);
+        assert_eq!(map.len(), 1);
+    }
+}
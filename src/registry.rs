@@ -0,0 +1,222 @@
+//! A store for values of many types, keyed by typed handles.
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+};
+
+use crate::{Handle, HandleKey, HandleMap, UntypedHandle};
+
+/// The type-erased operations of one [`HandleMap`] in a registry.
+trait Store: Any {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn len(&self) -> usize;
+    fn contains_key(&self, key: &HandleKey) -> bool;
+    fn collect_unreferenced(&mut self) -> usize;
+}
+
+impl<T: 'static> Store for HandleMap<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn len(&self) -> usize {
+        HandleMap::len(self)
+    }
+
+    fn contains_key(&self, key: &HandleKey) -> bool {
+        HandleMap::contains_key(self, key)
+    }
+
+    fn collect_unreferenced(&mut self) -> usize {
+        HandleMap::collect_unreferenced(self).len()
+    }
+}
+
+/// A collection of [`HandleMap`]s, one for each type of value stored in it.
+///
+/// The map for a value is picked by the type of its handle, so a
+/// `Handle<Texture>` looks up a `Texture` and a `Handle<Mesh>` a `Mesh`, even
+/// if both handles have the same key.
+///
+/// Values of any `'static` type can be stored, including ones that can't be
+/// shared between threads, like resources tied to a GPU context. In turn the
+/// registry is neither `Send` nor `Sync`.
+#[derive(Default)]
+pub struct HandleRegistry {
+    stores: HashMap<TypeId, Box<dyn Store>>,
+}
+
+impl std::fmt::Debug for HandleRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HandleRegistry")
+            .field("types", &self.stores.len())
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl HandleRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The total number of values of all types.
+    pub fn len(&self) -> usize {
+        self.stores.values().map(|store| store.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove every value of every type.
+    pub fn clear(&mut self) {
+        self.stores.clear();
+    }
+
+    /// Returns the map holding values of type `T`, if any have been inserted.
+    pub fn store<T: 'static>(&self) -> Option<&HandleMap<T>> {
+        self.stores
+            .get(&TypeId::of::<T>())
+            .and_then(|store| store.as_any().downcast_ref())
+    }
+
+    /// Returns the map holding values of type `T`, creating it if needed.
+    pub fn store_mut<T: 'static>(&mut self) -> &mut HandleMap<T> {
+        self.stores
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(HandleMap::<T>::new()))
+            .as_any_mut()
+            .downcast_mut()
+            .expect("store is keyed by the TypeId of its values")
+    }
+
+    fn existing_store_mut<T: 'static>(&mut self) -> Option<&mut HandleMap<T>> {
+        self.stores
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|store| store.as_any_mut().downcast_mut())
+    }
+
+    /// Insert a value, returning the handle and value previously stored under
+    /// the same key.
+    pub fn insert<T: 'static>(&mut self, handle: Handle<T>, value: T) -> Option<(Handle<T>, T)> {
+        self.store_mut().insert(handle, value)
+    }
+
+    pub fn contains<T: 'static>(&self, handle: &Handle<T>) -> bool {
+        self.store().is_some_and(|store| store.contains(handle))
+    }
+
+    /// Returns whether there is a value for an untyped handle, in the store
+    /// of the type the handle was created for.
+    pub fn contains_untyped(&self, handle: &UntypedHandle) -> bool {
+        self.stores
+            .get(&handle.type_id())
+            .is_some_and(|store| store.contains_key(handle.key()))
+    }
+
+    pub fn get<T: 'static>(&self, handle: &Handle<T>) -> Option<&T> {
+        self.store()?.get(handle)
+    }
+
+    pub fn get_mut<T: 'static>(&mut self, handle: &Handle<T>) -> Option<&mut T> {
+        self.existing_store_mut()?.get_mut(handle)
+    }
+
+    pub fn remove<T: 'static>(&mut self, handle: &Handle<T>) -> Option<T> {
+        self.existing_store_mut()?.remove(handle)
+    }
+
+    /// Remove every value, of any type, whose handle is no longer held by
+    /// anything outside of this registry, returning how many were removed.
+    pub fn collect_unreferenced(&mut self) -> usize {
+        self.stores
+            .values_mut()
+            .map(|store| store.collect_unreferenced())
+            .sum()
+    }
+}
+
+impl<T: 'static> std::ops::Index<&Handle<T>> for HandleRegistry {
+    type Output = T;
+
+    fn index(&self, handle: &Handle<T>) -> &T {
+        self.get(handle)
+            .unwrap_or_else(|| panic!("no entry for {handle:?}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Texture(u32);
+    #[derive(Debug, PartialEq)]
+    struct Mesh(u32);
+
+    #[test]
+    fn insert_get_and_remove_by_type() {
+        let mut registry = HandleRegistry::new();
+        let texture = Handle::<Texture>::new("tex");
+        assert!(registry.insert(texture.clone(), Texture(1)).is_none());
+        let (_, previous) = registry.insert(texture.clone(), Texture(2)).unwrap();
+        assert_eq!(previous, Texture(1));
+        assert_eq!(registry.get(&texture), Some(&Texture(2)));
+
+        registry.get_mut(&texture).unwrap().0 += 1;
+        assert_eq!(registry[&texture], Texture(3));
+        assert!(registry.get_mut(&Handle::<Mesh>::new("tex")).is_none());
+
+        assert_eq!(registry.remove(&texture), Some(Texture(3)));
+        assert!(!registry.contains(&texture));
+        assert!(registry.remove(&Handle::<Mesh>::new("tex")).is_none());
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn types_sharing_a_key_are_stored_apart() {
+        let mut registry = HandleRegistry::new();
+        let texture = Handle::<Texture>::new("grass");
+        let mesh = Handle::<Mesh>::new("grass");
+        registry.insert(texture.clone(), Texture(1));
+        registry.insert(mesh.clone(), Mesh(2));
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry[&texture], Texture(1));
+        assert_eq!(registry[&mesh], Mesh(2));
+        assert_eq!(registry.store::<Mesh>().map(HandleMap::len), Some(1));
+
+        assert!(registry.contains_untyped(&texture.clone().untyped()));
+        registry.remove(&mesh);
+        assert!(!registry.contains_untyped(&mesh.untyped()));
+        assert!(!registry.contains_untyped(&Handle::<u8>::new("grass").untyped()));
+    }
+
+    #[test]
+    fn collect_unreferenced_evicts_from_every_store() {
+        let mut registry = HandleRegistry::new();
+        let held = Handle::<Texture>::new("held");
+        registry.insert(held.clone(), Texture(1));
+        registry.insert(Handle::<Texture>::new("dropped"), Texture(2));
+        registry.insert(Handle::<Mesh>::new("dropped"), Mesh(3));
+        registry.insert(Handle::<Mesh>::from_static("static"), Mesh(4));
+        assert_eq!(registry.collect_unreferenced(), 2);
+        assert_eq!(registry.len(), 2);
+        assert!(registry.contains(&held));
+    }
+
+    #[test]
+    fn stores_values_that_are_not_send() {
+        let mut registry = HandleRegistry::new();
+        let handle = Handle::<Rc<u32>>::new("rc");
+        registry.insert(handle.clone(), Rc::new(1));
+        assert_eq!(*registry[&handle], 1);
+    }
+}
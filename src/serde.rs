@@ -0,0 +1,222 @@
+//! Serde support, enabled with the `serde` feature.
+//!
+//! Keys are serialized untagged: string keys as strings, integer keys as
+//! integers, composite keys as sequences, generational keys as a map of
+//! `index` and `generation`, labeled keys as a map of `base` and `label` and
+//! UUIDs as a map with a single `uuid` entry.
+//! Integer keys above `u64::MAX` only round-trip through formats that support
+//! 128-bit integers in self-describing deserialization (JSON does not).
+//! Deserialized handles get a fresh reference count.
+use serde::{
+    de::{self, MapAccess, SeqAccess, Visitor},
+    ser::{SerializeMap, SerializeSeq},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{Handle, HandleKey, KeyRef, LabeledKey};
+
+impl Serialize for HandleKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.as_key_ref() {
+            KeyRef::Str(s) => serializer.serialize_str(s),
+            KeyRef::Interned(sym) => serializer.serialize_str(sym.as_str()),
+            KeyRef::Static(key) => serializer.serialize_str(key.as_str()),
+            KeyRef::Number(n) => serializer.serialize_u64(n as u64),
+            KeyRef::U64(n) => serializer.serialize_u64(n),
+            KeyRef::U128(n) => serializer.serialize_u128(n),
+            KeyRef::I64(n) => serializer.serialize_i64(n),
+            #[cfg(feature = "uuid")]
+            KeyRef::Uuid(uuid) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("uuid", &uuid.hyphenated().to_string())?;
+                map.end()
+            }
+            KeyRef::Labeled(key) => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("base", key.base())?;
+                map.serialize_entry("label", key.label())?;
+                map.end()
+            }
+            KeyRef::Composite(keys) => {
+                let mut seq = serializer.serialize_seq(Some(keys.len()))?;
+                for key in keys {
+                    seq.serialize_element(key)?;
+                }
+                seq.end()
+            }
+            KeyRef::Generational { index, generation } => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("index", &index)?;
+                map.serialize_entry("generation", &generation)?;
+                map.end()
+            }
+        }
+    }
+}
+
+struct HandleKeyVisitor;
+
+impl<'de> Visitor<'de> for HandleKeyVisitor {
+    type Value = HandleKey;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a string, an integer, a sequence of keys or a map")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<HandleKey, E> {
+        Ok(HandleKey::from(v))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<HandleKey, E> {
+        Ok(HandleKey::from(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<HandleKey, E> {
+        Ok(usize::try_from(v).map_or(HandleKey::U64(v), HandleKey::Number))
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<HandleKey, E> {
+        Ok(u64::try_from(v).map_or(HandleKey::U128(v), HandleKey::from))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<HandleKey, E> {
+        Ok(usize::try_from(v).map_or(HandleKey::I64(v), HandleKey::Number))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<HandleKey, A::Error> {
+        let mut keys = vec![];
+        while let Some(key) = seq.next_element::<HandleKey>()? {
+            keys.push(key);
+        }
+        Ok(HandleKey::Composite(keys.into()))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<HandleKey, A::Error> {
+        const FIELDS: &[&str] = &["index", "generation", "base", "label", "uuid"];
+        let mut index = None;
+        let mut generation = None;
+        let mut base = None;
+        let mut label = None;
+        while let Some(field) = map.next_key::<String>()? {
+            match field.as_str() {
+                "index" => index = Some(map.next_value()?),
+                "generation" => generation = Some(map.next_value()?),
+                "base" => base = Some(map.next_value::<HandleKey>()?),
+                "label" => label = Some(map.next_value::<String>()?),
+                #[cfg(feature = "uuid")]
+                "uuid" => {
+                    let uuid = map.next_value::<String>()?;
+                    return uuid::Uuid::parse_str(&uuid)
+                        .map(HandleKey::Uuid)
+                        .map_err(de::Error::custom);
+                }
+                other => return Err(de::Error::unknown_field(other, FIELDS)),
+            }
+        }
+        if base.is_some() || label.is_some() {
+            return Ok(LabeledKey::new(
+                base.ok_or_else(|| de::Error::missing_field("base"))?,
+                label.ok_or_else(|| de::Error::missing_field("label"))?,
+            )
+            .into());
+        }
+        Ok(HandleKey::Generational {
+            index: index.ok_or_else(|| de::Error::missing_field("index"))?,
+            generation: generation.ok_or_else(|| de::Error::missing_field("generation"))?,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for HandleKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(HandleKeyVisitor)
+    }
+}
+
+impl<T, K: Serialize> Serialize for Handle<T, K> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.key.serialize(serializer)
+    }
+}
+
+impl<'de, T, K: Deserialize<'de>> Deserialize<'de> for Handle<T, K> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        K::deserialize(deserializer).map(Handle::with_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{StaticKey, Symbol};
+
+    fn keys() -> Vec<HandleKey> {
+        vec![
+            HandleKey::Str("grass"),
+            HandleKey::String("textures/water.png".into()),
+            HandleKey::Number(42),
+            HandleKey::U64(u64::MAX),
+            HandleKey::U128(7),
+            HandleKey::I64(-3),
+            #[cfg(feature = "uuid")]
+            HandleKey::Uuid(uuid::Uuid::from_u128(0x1234_5678_9abc_def0)),
+            HandleKey::Composite(Arc::from([HandleKey::Number(1), HandleKey::I64(-2)])),
+            HandleKey::from("scene.gltf").with_label("Mesh0"),
+            HandleKey::Interned(Symbol::intern("interned")),
+            HandleKey::Static(StaticKey::new("static")),
+            HandleKey::Generational {
+                index: 3,
+                generation: 9,
+            },
+        ]
+    }
+
+    #[test]
+    fn keys_round_trip_through_json() {
+        for key in keys() {
+            let json = serde_json::to_string(&key).unwrap();
+            let back: HandleKey = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, key, "{json}");
+        }
+    }
+
+    #[test]
+    fn keys_round_trip_through_ron() {
+        for key in keys().into_iter().chain([HandleKey::U128(u128::MAX)]) {
+            let ron = ron::to_string(&key).unwrap();
+            let back: HandleKey = ron::from_str(&ron).unwrap();
+            assert_eq!(back, key, "{ron}");
+        }
+    }
+
+    #[test]
+    fn keys_are_untagged() {
+        assert_eq!(
+            serde_json::to_string(&HandleKey::from("a")).unwrap(),
+            r#""a""#
+        );
+        assert_eq!(
+            serde_json::to_string(&HandleKey::from(5usize)).unwrap(),
+            "5"
+        );
+    }
+
+    #[test]
+    fn deserialized_handles_get_a_fresh_count() {
+        struct Tex;
+
+        let handle = Handle::<Tex>::new("tex");
+        let _clone = handle.clone();
+        let json = serde_json::to_string(&handle).unwrap();
+        let back: Handle<Tex> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, handle);
+        assert_eq!(back.strong_count(), Some(1));
+        assert_eq!(handle.strong_count(), Some(2));
+
+        let ron = ron::to_string(&handle).unwrap();
+        let back: Handle<Tex> = ron::from_str(&ron).unwrap();
+        assert_eq!(back.strong_count(), Some(1));
+    }
+}
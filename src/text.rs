@@ -0,0 +1,382 @@
+//! The canonical text form of keys and handles.
+//!
+//! * string keys are written as their text, with `\`, `#` and a leading `(`
+//!   escaped by a `\` (and `,` and `)` too, inside composite keys)
+//! * integer keys are written `#42` or `#-42`
+//! * generational keys are written `#index` `v` `generation`, e.g. `#3v1`
+//! * UUID keys are written `#{67e55044-10b1-426f-9247-bb680e5fe0c8}`
+//! * composite keys are written `(a,#1)`
+//! * labeled keys are written `base#label`; if the base is the empty string
+//!   and the label starts with a digit, `-` or `{`, that character is escaped
+use std::{fmt::Write, iter::Peekable, str::Chars};
+
+use crate::{Handle, HandleKey, KeyRef, LabeledKey};
+
+/// An error parsing a [`HandleKey`](enum@HandleKey) from text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseKeyError {
+    reason: String,
+}
+
+impl std::fmt::Display for ParseKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid handle key: {}", self.reason)
+    }
+}
+
+impl std::error::Error for ParseKeyError {}
+
+impl ParseKeyError {
+    fn new(reason: impl Into<String>) -> Self {
+        ParseKeyError {
+            reason: reason.into(),
+        }
+    }
+}
+
+fn write_text(
+    f: &mut std::fmt::Formatter<'_>,
+    text: &str,
+    atom: bool,
+    nested: bool,
+) -> std::fmt::Result {
+    for (i, c) in text.chars().enumerate() {
+        let escape = match c {
+            '\\' | '#' => true,
+            '(' => atom && i == 0,
+            ',' | ')' => nested,
+            _ => false,
+        };
+        if escape {
+            f.write_char('\\')?;
+        }
+        f.write_char(c)?;
+    }
+    Ok(())
+}
+
+fn write_key(f: &mut std::fmt::Formatter<'_>, key: KeyRef<'_>, nested: bool) -> std::fmt::Result {
+    match key {
+        KeyRef::Str(s) => write_text(f, s, true, nested),
+        KeyRef::Interned(sym) => write_text(f, sym.as_str(), true, nested),
+        KeyRef::Static(key) => write_text(f, key.as_str(), true, nested),
+        KeyRef::Number(n) => write!(f, "#{n}"),
+        KeyRef::U64(n) => write!(f, "#{n}"),
+        KeyRef::U128(n) => write!(f, "#{n}"),
+        KeyRef::I64(n) => write!(f, "#{n}"),
+        #[cfg(feature = "uuid")]
+        KeyRef::Uuid(uuid) => write!(f, "#{{{}}}", uuid.hyphenated()),
+        KeyRef::Generational { index, generation } => write!(f, "#{index}v{generation}"),
+        KeyRef::Composite(keys) => {
+            f.write_char('(')?;
+            for (i, key) in keys.iter().enumerate() {
+                if i > 0 {
+                    f.write_char(',')?;
+                }
+                write_key(f, key.as_key_ref(), true)?;
+            }
+            f.write_char(')')
+        }
+        KeyRef::Labeled(key) => {
+            write_key(f, key.base().as_key_ref(), nested)?;
+            f.write_char('#')?;
+            // Otherwise `#` and the label would read as a number.
+            if key.base().as_str() == Some("") && key.label().starts_with(starts_number) {
+                f.write_char('\\')?;
+            }
+            write_text(f, key.label(), false, nested)
+        }
+    }
+}
+
+impl std::fmt::Display for HandleKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_key(f, self.as_key_ref(), false)
+    }
+}
+
+impl std::fmt::Display for KeyRef<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_key(f, *self, false)
+    }
+}
+
+impl std::fmt::Display for LabeledKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_key(f, KeyRef::Labeled(self), false)
+    }
+}
+
+impl<T, K: std::fmt::Display> std::fmt::Display for Handle<T, K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.key.fmt(f)
+    }
+}
+
+fn starts_number(c: char) -> bool {
+    c.is_ascii_digit() || c == '-' || c == '{'
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn eat(&mut self, c: char) -> bool {
+        self.chars.next_if_eq(&c).is_some()
+    }
+
+    fn key(&mut self, nested: bool) -> Result<HandleKey, ParseKeyError> {
+        let mut key = self.atom(nested)?;
+        while self.eat('#') {
+            key = LabeledKey::new(key, self.text(nested)?).into();
+        }
+        Ok(key)
+    }
+
+    /// Returns whether the next `#` starts a number, rather than the label of
+    /// an empty string key.
+    fn at_number(&self) -> bool {
+        let mut ahead = self.chars.clone();
+        ahead.next() == Some('#') && ahead.next().is_some_and(starts_number)
+    }
+
+    fn atom(&mut self, nested: bool) -> Result<HandleKey, ParseKeyError> {
+        if self.at_number() {
+            self.chars.next();
+            self.number()
+        } else if self.eat('(') {
+            let mut keys = vec![];
+            if !self.eat(')') {
+                loop {
+                    keys.push(self.key(true)?);
+                    if self.eat(')') {
+                        break;
+                    }
+                    if !self.eat(',') {
+                        return Err(ParseKeyError::new("unclosed composite key"));
+                    }
+                }
+            }
+            Ok(HandleKey::Composite(keys.into()))
+        } else {
+            self.text(nested).map(HandleKey::String)
+        }
+    }
+
+    fn text(&mut self, nested: bool) -> Result<String, ParseKeyError> {
+        let mut text = String::new();
+        while let Some(c) = self.chars.peek().copied() {
+            match c {
+                '#' => break,
+                ',' | ')' if nested => break,
+                '\\' => {
+                    self.chars.next();
+                    let escaped = self
+                        .chars
+                        .next()
+                        .ok_or_else(|| ParseKeyError::new("trailing escape"))?;
+                    text.push(escaped);
+                }
+                c => {
+                    self.chars.next();
+                    text.push(c);
+                }
+            }
+        }
+        Ok(text)
+    }
+
+    fn number(&mut self) -> Result<HandleKey, ParseKeyError> {
+        if self.eat('{') {
+            let mut uuid = String::new();
+            loop {
+                match self.chars.next() {
+                    Some('}') => break,
+                    Some(c) => uuid.push(c),
+                    None => return Err(ParseKeyError::new("unclosed UUID")),
+                }
+            }
+            return parse_uuid(&uuid);
+        }
+
+        let mut digits = String::new();
+        if self.eat('-') {
+            digits.push('-');
+        }
+        while let Some(c) = self.chars.next_if(char::is_ascii_digit) {
+            digits.push(c);
+        }
+        let invalid = |_| ParseKeyError::new(format!("invalid number {digits:?}"));
+        if self.eat('v') {
+            let mut generation = String::new();
+            while let Some(c) = self.chars.next_if(char::is_ascii_digit) {
+                generation.push(c);
+            }
+            return Ok(HandleKey::Generational {
+                index: digits.parse().map_err(invalid)?,
+                generation: generation.parse().map_err(|_| {
+                    ParseKeyError::new(format!("invalid generation {generation:?}"))
+                })?,
+            });
+        }
+        if digits.starts_with('-') {
+            return digits.parse().map(HandleKey::I64).map_err(invalid);
+        }
+        let n: u128 = digits.parse().map_err(invalid)?;
+        Ok(if let Ok(n) = usize::try_from(n) {
+            HandleKey::Number(n)
+        } else if let Ok(n) = u64::try_from(n) {
+            HandleKey::U64(n)
+        } else {
+            HandleKey::U128(n)
+        })
+    }
+}
+
+#[cfg(feature = "uuid")]
+fn parse_uuid(uuid: &str) -> Result<HandleKey, ParseKeyError> {
+    uuid::Uuid::parse_str(uuid)
+        .map(HandleKey::Uuid)
+        .map_err(|e| ParseKeyError::new(e.to_string()))
+}
+
+#[cfg(not(feature = "uuid"))]
+fn parse_uuid(_: &str) -> Result<HandleKey, ParseKeyError> {
+    Err(ParseKeyError::new("UUID keys require the `uuid` feature"))
+}
+
+impl std::str::FromStr for HandleKey {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            chars: s.chars().peekable(),
+        };
+        let key = parser.key(false)?;
+        match parser.chars.next() {
+            None => Ok(key),
+            Some(c) => Err(ParseKeyError::new(format!("unexpected {c:?}"))),
+        }
+    }
+}
+
+impl<T, K: std::str::FromStr> std::str::FromStr for Handle<T, K> {
+    type Err = K::Err;
+
+    /// Parses a tracked handle.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Handle::with_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StaticKey, Symbol};
+
+    fn round_trip(key: HandleKey, text: &str) {
+        assert_eq!(key.to_string(), text);
+        assert_eq!(text.parse::<HandleKey>(), Ok(key), "{text}");
+    }
+
+    #[test]
+    fn string_keys_round_trip() {
+        round_trip("textures/grass.png".into(), "textures/grass.png");
+        round_trip("".into(), "");
+        round_trip(HandleKey::Interned(Symbol::intern("sym")), "sym");
+        round_trip(HandleKey::Static(StaticKey::new("static")), "static");
+    }
+
+    #[test]
+    fn escapes_round_trip() {
+        round_trip(r"a\b".into(), r"a\\b");
+        round_trip("#42".into(), r"\#42");
+        round_trip("(a,b)".into(), r"\(a,b)");
+        round_trip("a(b)".into(), "a(b)");
+        round_trip(HandleKey::composite(["a,b", "(c)"]), r"(a\,b,\(c\))");
+    }
+
+    #[test]
+    fn integer_keys_round_trip() {
+        round_trip(42usize.into(), "#42");
+        round_trip((-42i64).into(), "#-42");
+        round_trip(u64::MAX.into(), &format!("#{}", u64::MAX));
+        round_trip(u128::MAX.into(), &format!("#{}", u128::MAX));
+    }
+
+    #[test]
+    fn generational_keys_round_trip() {
+        round_trip(
+            HandleKey::Generational {
+                index: 3,
+                generation: 1,
+            },
+            "#3v1",
+        );
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn uuid_keys_round_trip() {
+        let uuid = uuid::Uuid::from_u128(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8);
+        round_trip(uuid.into(), "#{67e55044-10b1-426f-9247-bb680e5fe0c8}");
+    }
+
+    #[test]
+    fn composite_keys_round_trip() {
+        round_trip(HandleKey::from((1usize, "chunk")), "(#1,chunk)");
+        round_trip(HandleKey::composite(Vec::<HandleKey>::new()), "()");
+        round_trip(
+            HandleKey::composite([HandleKey::from((1usize, 2usize)), "x".into()]),
+            "((#1,#2),x)",
+        );
+    }
+
+    #[test]
+    fn labeled_keys_round_trip() {
+        round_trip(
+            HandleKey::from("scene.gltf").with_label("Mesh0"),
+            "scene.gltf#Mesh0",
+        );
+        round_trip(
+            HandleKey::from("scene.gltf")
+                .with_label("Mesh0")
+                .with_label("Primitive1"),
+            "scene.gltf#Mesh0#Primitive1",
+        );
+        round_trip(HandleKey::from(7usize).with_label("a#b"), r"#7#a\#b");
+        round_trip(
+            HandleKey::composite([HandleKey::from("m.gltf").with_label("a,b"), "c".into()]),
+            r"(m.gltf#a\,b,c)",
+        );
+        round_trip(HandleKey::from("").with_label("42"), r"#\42");
+        round_trip(HandleKey::from("").with_label("-1"), r"#\-1");
+        round_trip(HandleKey::from("").with_label("x"), "#x");
+        round_trip(HandleKey::from("").with_label(""), "#");
+        round_trip(
+            HandleKey::from("").with_label("1").with_label("2"),
+            r"#\1#2",
+        );
+        round_trip(
+            HandleKey::composite([HandleKey::from("").with_label("1")]),
+            r"(#\1)",
+        );
+    }
+
+    #[test]
+    fn invalid_keys_are_rejected() {
+        for text in ["(a", "(a,b", "#-", "#1vx", "a\\", "(a)b", "#{abc"] {
+            assert!(text.parse::<HandleKey>().is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn handles_parse_with_a_fresh_count() {
+        struct Tex;
+
+        let handle: Handle<Tex> = "scene.gltf#Mesh0".parse().unwrap();
+        assert_eq!(handle.to_string(), "scene.gltf#Mesh0");
+        assert_eq!(handle.strong_count(), Some(1));
+    }
+}
@@ -0,0 +1,196 @@
+//! Handles whose type is only known at runtime.
+use std::{
+    any::{type_name, TypeId},
+    hash::Hash,
+    marker::PhantomData,
+    sync::Arc,
+};
+
+use crate::{Handle, HandleKey, RefCount};
+
+/// A [`Handle`] with its type erased, created with [`Handle::untyped`].
+///
+/// Untyped handles of any asset type can be kept in the same collection. The
+/// type is remembered, so an untyped handle only turns back into a handle of
+/// the type it was created from, and two untyped handles are only equal if
+/// both their keys and their types match.
+///
+/// An untyped handle shares the reference count of the handle it was created
+/// from, so it keeps entries alive just like the typed handle would.
+pub struct UntypedHandle<K = HandleKey> {
+    // Underlying key used for comparison
+    key: K,
+    // Used to count how many things own a clone of the handle.
+    count: Option<Arc<RefCount>>,
+    type_id: TypeId,
+    type_name: &'static str,
+}
+
+impl<K: std::fmt::Debug> std::fmt::Debug for UntypedHandle<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(&format!("UntypedHandle<{}>", self.type_name))
+            .field("key", &self.key)
+            .field(
+                "references",
+                &format!("{:?}", self.count.as_ref().map(Arc::strong_count)),
+            )
+            .finish()
+    }
+}
+
+impl<K: Clone> Clone for UntypedHandle<K> {
+    fn clone(&self) -> Self {
+        UntypedHandle {
+            key: self.key.clone(),
+            count: self.count.clone(),
+            type_id: self.type_id,
+            type_name: self.type_name,
+        }
+    }
+}
+
+impl<K: Hash> Hash for UntypedHandle<K> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+        self.type_id.hash(state);
+    }
+}
+
+impl<K: PartialEq> PartialEq for UntypedHandle<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.type_id == other.type_id && self.key == other.key
+    }
+}
+
+impl<K: Eq> Eq for UntypedHandle<K> {}
+
+impl<K> UntypedHandle<K> {
+    /// Returns the key this handle is compared and hashed by.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Consumes the handle, returning its key.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Returns whether this handle counts its references.
+    pub fn is_tracked(&self) -> bool {
+        self.count.is_some()
+    }
+
+    /// Returns the number of strong handles, or `None` if the handle is
+    /// untracked.
+    pub fn strong_count(&self) -> Option<usize> {
+        self.count.as_ref().map(Arc::strong_count)
+    }
+
+    /// The [`TypeId`] of the type this handle was created for.
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    /// The name of the type this handle was created for, for diagnostics.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns whether this handle was created from a `Handle<T>`.
+    pub fn is<T: 'static>(&self) -> bool {
+        self.type_id == TypeId::of::<T>()
+    }
+
+    /// Turn this back into a typed handle, or give it back if it is not a
+    /// handle to `T`.
+    pub fn into_typed<T: 'static>(self) -> Result<Handle<T, K>, Self> {
+        if !self.is::<T>() {
+            return Err(self);
+        }
+        Ok(Handle {
+            key: self.key,
+            count: self.count,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<K: Clone> UntypedHandle<K> {
+    /// Returns a typed handle to the same key, or `None` if this is not a
+    /// handle to `T`.
+    ///
+    /// The typed handle shares this handle's reference count.
+    pub fn typed<T: 'static>(&self) -> Option<Handle<T, K>> {
+        self.clone().into_typed().ok()
+    }
+}
+
+impl<T: 'static, K> Handle<T, K> {
+    /// Erase the type of this handle.
+    pub fn untyped(self) -> UntypedHandle<K> {
+        UntypedHandle {
+            key: self.key,
+            count: self.count,
+            type_id: TypeId::of::<T>(),
+            type_name: type_name::<T>(),
+        }
+    }
+}
+
+impl<T: 'static, K> From<Handle<T, K>> for UntypedHandle<K> {
+    fn from(handle: Handle<T, K>) -> Self {
+        handle.untyped()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    struct Texture;
+    struct Mesh;
+
+    #[test]
+    fn only_types_back_into_the_original_type() {
+        let untyped = Handle::<Texture>::new("tex").untyped();
+        assert!(untyped.is::<Texture>());
+        assert!(!untyped.is::<Mesh>());
+        assert!(untyped.type_name().ends_with("Texture"));
+        assert_eq!(untyped.typed::<Texture>(), Some(Handle::new("tex")));
+        assert!(untyped.typed::<Mesh>().is_none());
+
+        let untyped = untyped.into_typed::<Mesh>().unwrap_err();
+        assert_eq!(untyped.type_id(), TypeId::of::<Texture>());
+        assert_eq!(
+            untyped.into_typed::<Texture>().unwrap().key().as_str(),
+            Some("tex")
+        );
+    }
+
+    #[test]
+    fn equality_and_hashing_include_the_type() {
+        let texture = Handle::<Texture>::new("a").untyped();
+        let mesh = Handle::<Mesh>::new("a").untyped();
+        assert_ne!(texture, mesh);
+        assert_eq!(texture, Handle::<Texture>::new("a").untyped());
+
+        let set = HashSet::from([texture.clone(), mesh, Handle::<Texture>::new("a").untyped()]);
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&texture));
+    }
+
+    #[test]
+    fn shares_the_count_of_the_original_handle() {
+        let handle = Handle::<Texture>::new("tex");
+        let untyped = UntypedHandle::from(handle.clone());
+        assert_eq!(handle.strong_count(), Some(2));
+        let typed = untyped.typed::<Texture>().unwrap();
+        assert_eq!(untyped.strong_count(), Some(3));
+        drop((handle, typed));
+        assert_eq!(untyped.strong_count(), Some(1));
+        assert!(untyped.is_tracked());
+        assert!(!Handle::<Texture>::from_static("tex").untyped().is_tracked());
+    }
+}
@@ -0,0 +1,173 @@
+//! Handles that do not keep their referent alive.
+use std::{
+    hash::Hash,
+    marker::PhantomData,
+    sync::{Arc, Weak},
+};
+
+use crate::{Handle, HandleKey, RefCount};
+
+/// A non-owning version of [`Handle`], created with [`Handle::downgrade`].
+///
+/// A `WeakHandle` does not count towards the handle's references, so holding
+/// one does not keep an entry from being collected. Like `Weak`, it can be
+/// upgraded back into a `Handle` as long as some strong handle still exists.
+pub struct WeakHandle<T, K = HandleKey> {
+    // Underlying key used for comparison
+    key: K,
+    // Weak reference to the count of the handle this was downgraded from.
+    count: Option<Weak<RefCount>>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T, K: std::fmt::Debug> std::fmt::Debug for WeakHandle<T, K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(&format!("WeakHandle<{}>", std::any::type_name::<T>()))
+            .field("key", &self.key)
+            .field(
+                "references",
+                &format!("{:?}", self.count.as_ref().map(Weak::strong_count)),
+            )
+            .finish()
+    }
+}
+
+impl<T, K: Clone> Clone for WeakHandle<T, K> {
+    fn clone(&self) -> Self {
+        WeakHandle {
+            key: self.key.clone(),
+            count: self.count.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T, K: Hash> Hash for WeakHandle<T, K> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
+impl<T, K: PartialEq> PartialEq for WeakHandle<T, K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<T, K: Eq> Eq for WeakHandle<T, K> {}
+
+impl<T, K: PartialOrd> PartialOrd for WeakHandle<T, K> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.key.partial_cmp(&other.key)
+    }
+}
+
+impl<T, K: Ord> Ord for WeakHandle<T, K> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl<T, K> WeakHandle<T, K> {
+    /// Returns the key this handle is compared and hashed by.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Returns the number of strong handles, or `None` if the handle is
+    /// untracked.
+    pub fn strong_count(&self) -> Option<usize> {
+        self.count.as_ref().map(Weak::strong_count)
+    }
+}
+
+impl<T, K: Clone> WeakHandle<T, K> {
+    /// Attempt to upgrade into a strong handle.
+    ///
+    /// Returns `None` if every strong handle has been dropped. Weak handles to
+    /// untracked handles always upgrade.
+    pub fn upgrade(&self) -> Option<Handle<T, K>> {
+        let count = match self.count.as_ref() {
+            Some(count) => Some(count.upgrade()?),
+            None => None,
+        };
+        Some(Handle {
+            key: self.key.clone(),
+            count,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<T, K: Clone> Handle<T, K> {
+    /// Create a [`WeakHandle`] to the same key.
+    pub fn downgrade(&self) -> WeakHandle<T, K> {
+        WeakHandle {
+            key: self.key.clone(),
+            count: self.count.as_ref().map(Arc::downgrade),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{HandleArena, HandleMap, OrderedHandleMap};
+
+    use super::*;
+
+    #[test]
+    fn upgrades_while_a_strong_handle_exists() {
+        let handle = Handle::<u32>::new("tex");
+        let weak = handle.downgrade();
+        assert_eq!(weak.key(), handle.key());
+        assert_eq!(weak.strong_count(), Some(1));
+        let upgraded = weak.upgrade().unwrap();
+        assert_eq!(upgraded, handle);
+        assert_eq!(weak.strong_count(), Some(2));
+        drop((handle, upgraded));
+        assert_eq!(weak.strong_count(), Some(0));
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn untracked_handles_always_upgrade() {
+        let weak = Handle::<u32>::from_static("tex").downgrade();
+        assert_eq!(weak.strong_count(), None);
+        assert_eq!(weak.upgrade(), Some(Handle::from_static("tex")));
+    }
+
+    #[test]
+    fn weak_handles_do_not_keep_entries_referenced() {
+        let mut map = HandleMap::<u32>::new();
+        let weak = {
+            let handle = Handle::new("tex");
+            map.insert(handle.clone(), 1);
+            handle.downgrade()
+        };
+        assert_eq!(weak.strong_count(), Some(1));
+        let evicted = map.collect_unreferenced();
+        assert_eq!(evicted.len(), 1);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn weak_handles_to_collected_entries_do_not_upgrade() {
+        let mut ordered = OrderedHandleMap::<u32>::new();
+        let weak = {
+            let handle = Handle::new("tex");
+            ordered.insert(handle.clone(), 1);
+            handle.downgrade()
+        };
+        let evicted = ordered.collect_unreferenced();
+        assert!(weak.upgrade().is_none());
+        assert_eq!(evicted[0].0.strong_count(), Some(1));
+
+        let mut arena = HandleArena::new();
+        let weak = arena.insert(1).downgrade();
+        let evicted = arena.collect_unreferenced();
+        assert!(weak.upgrade().is_none());
+        assert!(arena.is_empty());
+        assert_eq!(evicted[0].0.strong_count(), Some(1));
+    }
+}